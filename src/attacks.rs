@@ -0,0 +1,118 @@
+use fen::{Color, Fen, Piece, PieceKind};
+use parsers::{File, Rank, Square};
+
+pub(crate) const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+pub(crate) const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+pub(crate) const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+pub(crate) const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+pub(crate) fn offset(square: Square, file_offset: i8, rank_offset: i8) -> Option<Square> {
+    let file = square.file.index() as i8 + file_offset;
+    let rank = square.rank.index() as i8 + rank_offset;
+    if file < 0 || rank < 0 {
+        return None;
+    }
+
+    Some(Square {
+        file: File::from_index(file as usize)?,
+        rank: Rank::from_index(rank as usize)?,
+    })
+}
+
+pub(crate) fn all_squares() -> Vec<Square> {
+    Rank::ALL
+        .iter()
+        .flat_map(|&rank| File::ALL.iter().map(move |&file| Square { file, rank }))
+        .collect()
+}
+
+pub(crate) fn pawn_direction(color: Color) -> i8 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+pub(crate) fn king_square(fen: &Fen, color: Color) -> Option<Square> {
+    let king = Piece {
+        color,
+        kind: PieceKind::King,
+    };
+
+    all_squares()
+        .into_iter()
+        .find(|&square| fen.piece_at(square) == Some(king))
+}
+
+/// Whether a piece of color `by` attacks `target`.
+pub(crate) fn is_attacked(fen: &Fen, target: Square, by: Color) -> bool {
+    let is = |square: Option<Square>, kinds: &[PieceKind]| {
+        match square.and_then(|s| fen.piece_at(s)) {
+            Some(piece) => piece.color == by && kinds.contains(&piece.kind),
+            None => false,
+        }
+    };
+
+    let pawn_rank_offset = -pawn_direction(by);
+    if is(offset(target, -1, pawn_rank_offset), &[PieceKind::Pawn])
+        || is(offset(target, 1, pawn_rank_offset), &[PieceKind::Pawn])
+    {
+        return true;
+    }
+
+    let knight = KNIGHT_OFFSETS
+        .iter()
+        .any(|&(f, r)| is(offset(target, f, r), &[PieceKind::Knight]));
+    let king = KING_OFFSETS
+        .iter()
+        .any(|&(f, r)| is(offset(target, f, r), &[PieceKind::King]));
+    if knight || king {
+        return true;
+    }
+
+    let sliders = [
+        (&ROOK_DIRECTIONS, [PieceKind::Rook, PieceKind::Queen]),
+        (&BISHOP_DIRECTIONS, [PieceKind::Bishop, PieceKind::Queen]),
+    ];
+    for &(directions, ref kinds) in sliders.iter() {
+        for &(f, r) in directions.iter() {
+            let mut square = offset(target, f, r);
+            while let Some(s) = square {
+                if fen.piece_at(s).is_some() {
+                    if is(square, kinds) {
+                        return true;
+                    }
+                    break;
+                }
+                square = offset(s, f, r);
+            }
+        }
+    }
+
+    false
+}
+
+pub(crate) fn is_in_check(fen: &Fen, color: Color) -> bool {
+    match king_square(fen, color) {
+        Some(king) => is_attacked(fen, king, color.opposite()),
+        None => false,
+    }
+}
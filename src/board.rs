@@ -0,0 +1,718 @@
+use attacks::{
+    all_squares, is_attacked, is_in_check, king_square, offset, pawn_direction, BISHOP_DIRECTIONS,
+    KING_OFFSETS, KNIGHT_OFFSETS, ROOK_DIRECTIONS,
+};
+use fen::{CastlingMode, CastlingSide, Color, Fen, FenError, Piece, PieceKind, STARTING_FEN};
+use parsers::{File, Move, PromotionPiece, Rank, Square};
+use position::Position;
+use std::collections::HashSet;
+
+/// How a game ended.
+///
+/// The fifty-move rule and threefold repetition are reported as soon as
+/// they apply, as an arbiter would when either player claims the draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate { winner: Color },
+    Stalemate,
+    FiftyMoves,
+    ThreefoldRepetition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    InvalidFen(FenError),
+    /// The move is not legal in the position described by the FEN.
+    IllegalMove { illegal_move: Move, fen: Fen },
+}
+
+impl From<FenError> for BoardError {
+    fn from(e: FenError) -> BoardError {
+        BoardError::InvalidFen(e)
+    }
+}
+
+/// What makes two positions the same for the repetition rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RepetitionKey {
+    fen: Fen,
+}
+
+/// A chess position that moves can be played on.
+///
+/// Castling moves are read and generated in the board's `CastlingMode`,
+/// which is `CastlingMode::Standard` unless changed.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::Board;
+///
+/// let mut board = Board::new();
+/// assert_eq!(board.legal_moves().len(), 20);
+///
+/// board.play("e2e4".parse().unwrap()).unwrap();
+/// assert!(board.play("e2e4".parse().unwrap()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    fen: Fen,
+    mode: CastlingMode,
+    history: Vec<RepetitionKey>,
+    /// The legal moves of the position, generated once each time it changes.
+    legal: Vec<Move>,
+}
+
+const PROMOTION_PIECES: [PromotionPiece; 4] = [
+    PromotionPiece::Queen,
+    PromotionPiece::Rook,
+    PromotionPiece::Bishop,
+    PromotionPiece::Knight,
+];
+
+fn promotion_kind(piece: PromotionPiece) -> PieceKind {
+    match piece {
+        PromotionPiece::Knight => PieceKind::Knight,
+        PromotionPiece::Bishop => PieceKind::Bishop,
+        PromotionPiece::Rook => PieceKind::Rook,
+        PromotionPiece::Queen => PieceKind::Queen,
+    }
+}
+
+fn set(fen: &mut Fen, square: Square, piece: Option<Piece>) {
+    fen.placement[square.rank.index()][square.file.index()] = piece;
+}
+
+fn push_pawn_move(moves: &mut Vec<Move>, from: Square, to: Square) {
+    if to.rank == Rank::First || to.rank == Rank::Eight {
+        for &piece in PROMOTION_PIECES.iter() {
+            moves.push(Move {
+                from,
+                to,
+                promotion_piece: Some(piece),
+            });
+        }
+    } else {
+        moves.push(Move {
+            from,
+            to,
+            promotion_piece: None,
+        });
+    }
+}
+
+fn pawn_moves(fen: &Fen, from: Square, us: Color, moves: &mut Vec<Move>) {
+    let direction = pawn_direction(us);
+    let start_rank = match us {
+        Color::White => Rank::Second,
+        Color::Black => Rank::Seventh,
+    };
+
+    if let Some(to) = offset(from, 0, direction) {
+        if fen.piece_at(to).is_none() {
+            push_pawn_move(moves, from, to);
+
+            if let Some(to) = offset(from, 0, 2 * direction) {
+                if from.rank == start_rank && fen.piece_at(to).is_none() {
+                    push_pawn_move(moves, from, to);
+                }
+            }
+        }
+    }
+
+    for &file_offset in [-1, 1].iter() {
+        if let Some(to) = offset(from, file_offset, direction) {
+            match fen.piece_at(to) {
+                Some(piece) if piece.color != us => push_pawn_move(moves, from, to),
+                None if fen.en_passant == Some(to) => push_pawn_move(moves, from, to),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn step_moves(fen: &Fen, from: Square, us: Color, offsets: &[(i8, i8)], moves: &mut Vec<Move>) {
+    for &(f, r) in offsets {
+        if let Some(to) = offset(from, f, r) {
+            if fen.piece_at(to).map(|p| p.color) != Some(us) {
+                moves.push(Move {
+                    from,
+                    to,
+                    promotion_piece: None,
+                });
+            }
+        }
+    }
+}
+
+fn slide_moves(
+    fen: &Fen,
+    from: Square,
+    us: Color,
+    directions: &[(i8, i8)],
+    moves: &mut Vec<Move>,
+) {
+    for &(f, r) in directions {
+        let mut square = offset(from, f, r);
+        while let Some(to) = square {
+            let occupant = fen.piece_at(to);
+            if occupant.map(|p| p.color) != Some(us) {
+                moves.push(Move {
+                    from,
+                    to,
+                    promotion_piece: None,
+                });
+            }
+            if occupant.is_some() {
+                break;
+            }
+            square = offset(to, f, r);
+        }
+    }
+}
+
+/// Castling works the same way in standard chess and in Chess960: every
+/// square the king and rook cross must be empty, apart from the two of
+/// them, and no square the king crosses may be attacked.
+fn castling_moves(fen: &Fen, us: Color, mode: CastlingMode, moves: &mut Vec<Move>) {
+    let rank = us.back_rank();
+    let them = us.opposite();
+    let square = |file: usize| Square {
+        file: File::ALL[file],
+        rank,
+    };
+
+    for &side in [CastlingSide::KingSide, CastlingSide::QueenSide].iter() {
+        let (m, rook) = match (fen.castling_move(side, mode), fen.castling.rook_file(us, side)) {
+            (Some(m), Some(rook)) => (m, rook.index()),
+            _ => continue,
+        };
+        let king = m.from.file.index();
+        let king_to = side.king_destination().index();
+        let rook_to = side.rook_destination().index();
+
+        let files = [king, rook, king_to, rook_to];
+        let first = *files.iter().min().unwrap();
+        let last = *files.iter().max().unwrap();
+        let empty = (first..last + 1)
+            .all(|f| f == king || f == rook || fen.piece_at(square(f)).is_none());
+
+        // The castling rook could hide an attack on the king's path.
+        let mut without = fen.clone();
+        set(&mut without, square(king), None);
+        set(&mut without, square(rook), None);
+        let safe = (king.min(king_to)..king.max(king_to) + 1)
+            .all(|f| !is_attacked(&without, square(f), them));
+
+        if empty && safe {
+            moves.push(m);
+        }
+    }
+}
+
+/// Moves that follow the movement rules, ignoring whether they leave the
+/// king in check.
+fn pseudo_legal_moves(fen: &Fen, mode: CastlingMode) -> Vec<Move> {
+    let us = fen.side_to_move;
+    let mut moves = Vec::new();
+
+    for from in all_squares() {
+        let piece = match fen.piece_at(from) {
+            Some(piece) if piece.color == us => piece,
+            _ => continue,
+        };
+
+        match piece.kind {
+            PieceKind::Pawn => pawn_moves(fen, from, us, &mut moves),
+            PieceKind::Knight => step_moves(fen, from, us, &KNIGHT_OFFSETS, &mut moves),
+            PieceKind::Bishop => slide_moves(fen, from, us, &BISHOP_DIRECTIONS, &mut moves),
+            PieceKind::Rook => slide_moves(fen, from, us, &ROOK_DIRECTIONS, &mut moves),
+            PieceKind::Queen => {
+                slide_moves(fen, from, us, &BISHOP_DIRECTIONS, &mut moves);
+                slide_moves(fen, from, us, &ROOK_DIRECTIONS, &mut moves);
+            }
+            PieceKind::King => {
+                step_moves(fen, from, us, &KING_OFFSETS, &mut moves);
+                castling_moves(fen, us, mode, &mut moves);
+            }
+        }
+    }
+
+    moves
+}
+
+fn castle(fen: &mut Fen, side: CastlingSide) {
+    let us = fen.side_to_move;
+    let rank = us.back_rank();
+    let square = |file| Square { file, rank };
+    let rook = fen.castling.rook_file(us, side).expect("castling without a rook");
+    let king = king_square(fen, us).expect("castling without a king");
+
+    set(fen, king, None);
+    set(fen, square(rook), None);
+    set(
+        fen,
+        square(side.king_destination()),
+        Some(Piece {
+            color: us,
+            kind: PieceKind::King,
+        }),
+    );
+    set(
+        fen,
+        square(side.rook_destination()),
+        Some(Piece {
+            color: us,
+            kind: PieceKind::Rook,
+        }),
+    );
+
+    fen.castling.set_rook_file(us, CastlingSide::KingSide, None);
+    fen.castling.set_rook_file(us, CastlingSide::QueenSide, None);
+    fen.en_passant = None;
+    fen.halfmove_clock += 1;
+}
+
+/// Plays a pseudo-legal move, updating every field of the FEN.
+fn apply(fen: &mut Fen, m: Move, mode: CastlingMode) {
+    let us = fen.side_to_move;
+
+    match m.castling_side(fen, mode) {
+        Some(side) => castle(fen, side),
+        None => move_piece(fen, m),
+    }
+
+    if us == Color::Black {
+        fen.fullmove_number += 1;
+    }
+    fen.side_to_move = us.opposite();
+}
+
+fn move_piece(fen: &mut Fen, m: Move) {
+    let piece = fen.piece_at(m.from).expect("no piece on the move's origin");
+    let captured = fen.piece_at(m.to);
+    let us = piece.color;
+    let is_pawn = piece.kind == PieceKind::Pawn;
+
+    if is_pawn && captured.is_none() && m.from.file != m.to.file {
+        // En passant: the captured pawn is next to the origin square.
+        set(
+            fen,
+            Square {
+                file: m.to.file,
+                rank: m.from.rank,
+            },
+            None,
+        );
+    }
+
+    let placed = match m.promotion_piece {
+        Some(promotion) => Piece {
+            color: us,
+            kind: promotion_kind(promotion),
+        },
+        None => piece,
+    };
+    set(fen, m.from, None);
+    set(fen, m.to, Some(placed));
+
+    // Moving the king loses both rights, and moving or capturing a rook
+    // loses the right to castle with it.
+    for &color in [Color::White, Color::Black].iter() {
+        for &side in [CastlingSide::KingSide, CastlingSide::QueenSide].iter() {
+            let rook = match fen.castling.rook_file(color, side) {
+                Some(file) => Square {
+                    file,
+                    rank: color.back_rank(),
+                },
+                None => continue,
+            };
+            let king_moved = color == us && piece.kind == PieceKind::King;
+            if king_moved || m.from == rook || m.to == rook {
+                fen.castling.set_rook_file(color, side, None);
+            }
+        }
+    }
+
+    let rank_distance = m.to.rank.index() as i8 - m.from.rank.index() as i8;
+    fen.en_passant = if is_pawn && rank_distance.abs() == 2 {
+        offset(m.from, 0, rank_distance / 2)
+    } else {
+        None
+    };
+
+    if is_pawn || captured.is_some() {
+        fen.halfmove_clock = 0;
+    } else {
+        fen.halfmove_clock += 1;
+    }
+}
+
+fn legal_moves(fen: &Fen, mode: CastlingMode) -> Vec<Move> {
+    let us = fen.side_to_move;
+
+    pseudo_legal_moves(fen, mode)
+        .into_iter()
+        .filter(|&m| {
+            let mut after = fen.clone();
+            apply(&mut after, m, mode);
+            !is_in_check(&after, us)
+        })
+        .collect()
+}
+
+impl Board {
+    /// The standard starting position.
+    pub fn new() -> Board {
+        Board::from_fen(STARTING_FEN.parse().unwrap()).unwrap()
+    }
+
+    pub fn from_fen(fen: Fen) -> Result<Board, BoardError> {
+        fen.validate()?;
+
+        let legal = legal_moves(&fen, CastlingMode::Standard);
+        Ok(Board {
+            fen,
+            mode: CastlingMode::Standard,
+            history: Vec::new(),
+            legal,
+        })
+    }
+
+    /// Sets up the position a "position" command describes, playing all
+    /// of its moves. Castling moves are read in `mode`.
+    pub fn from_position(position: &Position, mode: CastlingMode) -> Result<Board, BoardError> {
+        let mut board = match *position {
+            Position::StartPos { .. } => Board::new(),
+            Position::Fen { ref fen, .. } => Board::from_fen(fen.clone())?,
+        };
+        board.set_castling_mode(mode);
+
+        for &m in position.moves() {
+            board.play(m)?;
+        }
+
+        Ok(board)
+    }
+
+    pub fn fen(&self) -> &Fen {
+        &self.fen
+    }
+
+    pub fn castling_mode(&self) -> CastlingMode {
+        self.mode
+    }
+
+    pub fn set_castling_mode(&mut self, mode: CastlingMode) {
+        self.mode = mode;
+        self.legal = legal_moves(&self.fen, mode);
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.fen.side_to_move
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.fen.piece_at(square)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.legal.clone()
+    }
+
+    pub fn is_legal(&self, m: Move) -> bool {
+        self.legal.contains(&m)
+    }
+
+    /// Plays a move, or leaves the board untouched if it is illegal.
+    pub fn play(&mut self, m: Move) -> Result<(), BoardError> {
+        if !self.is_legal(m) {
+            return Err(BoardError::IllegalMove {
+                illegal_move: m,
+                fen: self.fen.clone(),
+            });
+        }
+
+        let key = self.repetition_key();
+        self.history.push(key);
+        apply(&mut self.fen, m, self.mode);
+        self.legal = legal_moves(&self.fen, self.mode);
+
+        Ok(())
+    }
+
+    pub fn is_check(&self) -> bool {
+        is_in_check(&self.fen, self.fen.side_to_move)
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal.is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal.is_empty()
+    }
+
+    pub fn is_fifty_moves(&self) -> bool {
+        self.fen.halfmove_clock >= 100
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        let key = self.repetition_key();
+        let earlier = self.history.iter().filter(|&k| *k == key).count();
+
+        earlier >= 2
+    }
+
+    /// Whether the game is over, and how.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.legal.is_empty() {
+            if self.is_check() {
+                Some(Outcome::Checkmate {
+                    winner: self.fen.side_to_move.opposite(),
+                })
+            } else {
+                Some(Outcome::Stalemate)
+            }
+        } else if self.is_fifty_moves() {
+            Some(Outcome::FiftyMoves)
+        } else if self.is_threefold_repetition() {
+            Some(Outcome::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// Positions repeat when the same pieces are on the same squares, with
+    /// the same side to move and the same possible moves. An en passant
+    /// square only matters when the capture can actually be played.
+    fn repetition_key(&self) -> RepetitionKey {
+        let mut fen = self.fen.clone();
+        fen.halfmove_clock = 0;
+        fen.fullmove_number = 1;
+
+        if let Some(target) = fen.en_passant {
+            let pawns: HashSet<Square> = all_squares()
+                .into_iter()
+                .filter(|&s| self.piece_at(s).map(|p| p.kind) == Some(PieceKind::Pawn))
+                .collect();
+            let can_capture = self.legal
+                .iter()
+                .any(|m| m.to == target && pawns.contains(&m.from));
+            if !can_capture {
+                fen.en_passant = None;
+            }
+        }
+
+        RepetitionKey { fen }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perft(board: &Board, depth: u32) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+
+        board
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let mut child = board.clone();
+                child.play(m).unwrap();
+                perft(&child, depth - 1)
+            })
+            .sum()
+    }
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen.parse().unwrap()).unwrap()
+    }
+
+    fn play(board: &mut Board, moves: &str) {
+        for m in moves.split_whitespace() {
+            board.play(m.parse().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn perft_test() {
+        assert_eq!(perft(&Board::new(), 3), 8902);
+
+        // Castling, en passant and promotions in every direction.
+        let kiwipete = board("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(perft(&kiwipete, 2), 2039);
+
+        let endgame = board("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+        assert_eq!(perft(&endgame, 3), 2812);
+
+        let promotions = board("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1");
+        assert_eq!(perft(&promotions, 2), 496);
+    }
+
+    #[test]
+    fn castling_test() {
+        let mut b = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        play(&mut b, "e1g1 e8c8");
+
+        assert_eq!(
+            b.fen().to_string(),
+            "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2"
+        );
+    }
+
+    #[test]
+    fn chess960_test() {
+        let mut b = board("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+        b.set_castling_mode(CastlingMode::Chess960);
+        assert_eq!(perft(&b, 1), 21);
+        assert_eq!(perft(&b, 2), 528);
+
+        // The white king is already on g1, so castling king side only moves
+        // the rook.
+        let mut b = board("1r4kr/8/8/8/8/8/8/1R4KR w KQkq - 0 1");
+        b.set_castling_mode(CastlingMode::Chess960);
+        play(&mut b, "g1h1");
+        assert_eq!(b.fen().to_string(), "1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 1 1");
+
+        // The rook on f1 now covers a square the black king would cross.
+        assert!(!b.is_legal("g8b8".parse().unwrap()));
+        assert!(b.is_legal("g8h8".parse().unwrap()));
+    }
+
+    #[test]
+    fn castling_mode_test() {
+        let mut b = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let e1g1: Move = "e1g1".parse().unwrap();
+        let e1h1: Move = "e1h1".parse().unwrap();
+
+        assert!(b.is_legal(e1g1));
+        assert!(!b.is_legal(e1h1));
+        assert_eq!(
+            e1g1.castling_side(b.fen(), CastlingMode::Standard),
+            Some(CastlingSide::KingSide)
+        );
+        assert_eq!(e1h1.castling_side(b.fen(), CastlingMode::Standard), None);
+
+        b.set_castling_mode(CastlingMode::Chess960);
+        assert!(!b.is_legal(e1g1));
+        assert!(b.is_legal(e1h1));
+        assert_eq!(e1g1.castling_side(b.fen(), CastlingMode::Chess960), None);
+        assert_eq!(
+            e1h1.castling_side(b.fen(), CastlingMode::Chess960),
+            Some(CastlingSide::KingSide)
+        );
+
+        play(&mut b, "e1h1 e8a8");
+        assert_eq!(b.fen().to_string(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+    }
+
+    #[test]
+    fn en_passant_test() {
+        let mut b = Board::new();
+        play(&mut b, "e2e4 a7a6 e4e5 d7d5");
+        assert_eq!(
+            b.fen().to_string(),
+            "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+        );
+
+        play(&mut b, "e5d6");
+        assert_eq!(
+            b.fen().to_string(),
+            "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+        );
+    }
+
+    #[test]
+    fn promotion_test() {
+        let mut b = board("8/P7/8/8/8/8/8/k6K w - - 0 1");
+        assert!(!b.is_legal("a7a8".parse().unwrap()));
+
+        let mut knight = b.clone();
+        play(&mut knight, "a7a8n");
+        assert_eq!(knight.fen().to_string(), "N7/8/8/8/8/8/8/k6K b - - 0 1");
+
+        play(&mut b, "a7a8q");
+        assert_eq!(b.fen().to_string(), "Q7/8/8/8/8/8/8/k6K b - - 0 1");
+    }
+
+    #[test]
+    fn illegal_move_test() {
+        let mut b = board("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1");
+        let m: Move = "e1d2".parse().unwrap();
+
+        assert_eq!(
+            b.play(m),
+            Err(BoardError::IllegalMove {
+                illegal_move: m,
+                fen: b.fen().clone(),
+            })
+        );
+    }
+
+    #[test]
+    fn checkmate_test() {
+        let mut b = Board::new();
+        play(&mut b, "f2f3 e7e5 g2g4");
+        assert_eq!(b.outcome(), None);
+
+        play(&mut b, "d8h4");
+        assert!(b.is_check());
+        assert!(b.is_checkmate());
+        assert_eq!(
+            b.outcome(),
+            Some(Outcome::Checkmate {
+                winner: Color::Black,
+            })
+        );
+    }
+
+    #[test]
+    fn stalemate_test() {
+        let b = board("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert!(b.is_stalemate());
+        assert_eq!(b.outcome(), Some(Outcome::Stalemate));
+    }
+
+    #[test]
+    fn fifty_moves_test() {
+        let mut b = board("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+        assert_eq!(b.outcome(), None);
+
+        play(&mut b, "a1a2");
+        assert_eq!(b.outcome(), Some(Outcome::FiftyMoves));
+    }
+
+    #[test]
+    fn threefold_repetition_test() {
+        let mut b = Board::new();
+        play(&mut b, "g1f3 g8f6 f3g1 f6g8 g1f3 g8f6 f3g1");
+        assert!(!b.is_threefold_repetition());
+
+        play(&mut b, "f6g8");
+        assert!(b.is_threefold_repetition());
+        assert_eq!(b.outcome(), Some(Outcome::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn from_position_test() {
+        let mut position = Position::startpos();
+        position.push("e2e4".parse().unwrap());
+        position.push("e7e5".parse().unwrap());
+
+        let b = Board::from_position(&position, CastlingMode::Standard).unwrap();
+        assert_eq!(
+            b.fen().to_string(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use fen::Color;
+use go_params::GoParams;
+use std::cmp;
+use std::time::{Duration, Instant};
+
+/// Time a player gets back for each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBonus {
+    /// Added after every move. This is the only bonus engines are told
+    /// about, with "winc" and "binc".
+    Fischer(Duration),
+    /// The time used for the move is given back, up to this much.
+    Bronstein(Duration),
+    /// The clock only starts running once this much time has passed.
+    SimpleDelay(Duration),
+}
+
+/// A stage of a time control: `moves` moves to be played in `time`, or the
+/// rest of the game if `moves` is `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimePeriod {
+    pub moves: Option<u32>,
+    pub time: Duration,
+}
+
+/// A player used more time than was left on their clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagFall {
+    pub side: Color,
+    /// The time charged for the move.
+    pub used: Duration,
+    /// The time that was left before the move.
+    pub remaining: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlayerClock {
+    remaining: Duration,
+    period: usize,
+    moves: u32,
+}
+
+/// The clocks of both players of a game.
+///
+/// `go_params` gives the remaining times to send with "go", and `start` and
+/// `stop` measure the time from "go" to "bestmove" and charge it to the side
+/// to move.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::{GameClock, TimeBonus};
+/// use std::time::Duration;
+///
+/// let mut clock = GameClock::sudden_death(Duration::from_secs(60))
+///     .bonus(TimeBonus::Fischer(Duration::from_secs(1)));
+/// assert_eq!(
+///     clock.go_params().to_string(),
+///     "wtime 60000 btime 60000 winc 1000 binc 1000"
+/// );
+///
+/// clock.record(Duration::from_secs(5)).unwrap();
+/// assert_eq!(clock.remaining(async_uci::Color::White), Duration::from_secs(56));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameClock {
+    periods: Vec<TimePeriod>,
+    bonus: Option<TimeBonus>,
+    lag_tolerance: Duration,
+    white: PlayerClock,
+    black: PlayerClock,
+    turn: Color,
+    started: Option<Instant>,
+}
+
+impl GameClock {
+    /// Both players get `time` for the whole game.
+    pub fn sudden_death(time: Duration) -> GameClock {
+        GameClock::with_periods(vec![TimePeriod { moves: None, time }])
+    }
+
+    /// Both players start with the time of the first period, and get the
+    /// time of the next one each time they complete a period's moves. The
+    /// last period repeats if it has a number of moves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `periods` is empty.
+    pub fn with_periods(periods: Vec<TimePeriod>) -> GameClock {
+        assert!(!periods.is_empty(), "a time control needs at least one period");
+
+        let clock = PlayerClock {
+            remaining: periods[0].time,
+            period: 0,
+            moves: 0,
+        };
+
+        GameClock {
+            periods,
+            bonus: None,
+            lag_tolerance: Duration::from_secs(0),
+            white: clock,
+            black: clock,
+            turn: Color::White,
+            started: None,
+        }
+    }
+
+    pub fn bonus(mut self, bonus: TimeBonus) -> GameClock {
+        self.bonus = Some(bonus);
+        self
+    }
+
+    /// Lets a move take this much longer than the remaining time before the
+    /// flag falls, to make up for the time lost talking to the engine.
+    pub fn lag_tolerance(mut self, tolerance: Duration) -> GameClock {
+        self.lag_tolerance = tolerance;
+        self
+    }
+
+    /// Sets whose clock runs first, which is White unless the game starts
+    /// from a position with Black to move.
+    pub fn first_to_move(mut self, side: Color) -> GameClock {
+        self.turn = side;
+        self
+    }
+
+    /// The side whose clock runs next.
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    pub fn remaining(&self, side: Color) -> Duration {
+        self.player(side).remaining
+    }
+
+    /// The number of moves `side` has to play before getting more time, if
+    /// the current period has a number of moves.
+    pub fn moves_to_go(&self, side: Color) -> Option<u32> {
+        let clock = self.player(side);
+        self.periods[clock.period]
+            .moves
+            .map(|moves| moves - clock.moves)
+    }
+
+    /// The clock parameters of the "go" command for the side to move.
+    pub fn go_params(&self) -> GoParams {
+        let mut params = GoParams::new()
+            .wtime(self.white.remaining)
+            .btime(self.black.remaining);
+
+        if let Some(TimeBonus::Fischer(increment)) = self.bonus {
+            params = params.winc(increment).binc(increment);
+        }
+        if let Some(moves) = self.moves_to_go(self.turn) {
+            params = params.movestogo(moves);
+        }
+
+        params
+    }
+
+    /// Starts the side to move's clock, right before "go" is sent.
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    /// Stops the running clock once "bestmove" arrived, and charges the time
+    /// since `start` as `record` does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock was not started.
+    pub fn stop(&mut self) -> Result<Duration, FlagFall> {
+        let started = self.started.take().expect("clock stopped before being started");
+        let elapsed = started.elapsed();
+
+        self.record(elapsed).map(|()| elapsed)
+    }
+
+    /// Charges `elapsed` to the side to move, applies the bonus and passes
+    /// the turn to the other side.
+    pub fn record(&mut self, elapsed: Duration) -> Result<(), FlagFall> {
+        let side = self.turn;
+        let bonus = self.bonus;
+        let lag_tolerance = self.lag_tolerance;
+        let periods = &self.periods;
+        let clock = match side {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        };
+
+        let used = match bonus {
+            Some(TimeBonus::SimpleDelay(delay)) => {
+                elapsed.checked_sub(delay).unwrap_or_default()
+            }
+            _ => elapsed,
+        };
+        if used > clock.remaining + lag_tolerance {
+            let remaining = clock.remaining;
+            clock.remaining = Duration::from_secs(0);
+            return Err(FlagFall {
+                side,
+                used,
+                remaining,
+            });
+        }
+        clock.remaining = clock.remaining.checked_sub(used).unwrap_or_default();
+
+        match bonus {
+            Some(TimeBonus::Fischer(increment)) => clock.remaining += increment,
+            Some(TimeBonus::Bronstein(delay)) => clock.remaining += cmp::min(used, delay),
+            _ => {}
+        }
+
+        clock.moves += 1;
+        if periods[clock.period].moves == Some(clock.moves) {
+            clock.moves = 0;
+            clock.period = cmp::min(clock.period + 1, periods.len() - 1);
+            clock.remaining += periods[clock.period].time;
+        }
+
+        self.turn = side.opposite();
+        Ok(())
+    }
+
+    /// The time control in the format of the PGN "TimeControl" tag, such as
+    /// "40/5400+30:1800+30". Only a Fischer increment can be written.
+    pub fn pgn_time_control(&self) -> String {
+        let increment = match self.bonus {
+            Some(TimeBonus::Fischer(increment)) => format!("+{}", increment.as_secs()),
+            _ => String::new(),
+        };
+
+        let periods: Vec<String> = self.periods
+            .iter()
+            .map(|period| match period.moves {
+                Some(moves) => format!("{}/{}{}", moves, period.time.as_secs(), increment),
+                None => format!("{}{}", period.time.as_secs(), increment),
+            })
+            .collect();
+        periods.join(":")
+    }
+
+    fn player(&self, side: Color) -> &PlayerClock {
+        match side {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn fischer_test() {
+        let mut clock = GameClock::sudden_death(secs(60)).bonus(TimeBonus::Fischer(secs(2)));
+        clock.record(secs(10)).unwrap();
+        clock.record(secs(1)).unwrap();
+
+        assert_eq!(clock.remaining(Color::White), secs(52));
+        assert_eq!(clock.remaining(Color::Black), secs(61));
+        assert_eq!(clock.turn(), Color::White);
+        assert_eq!(clock.pgn_time_control(), "60+2");
+    }
+
+    #[test]
+    fn delay_test() {
+        let mut bronstein = GameClock::sudden_death(secs(60)).bonus(TimeBonus::Bronstein(secs(3)));
+        bronstein.record(secs(10)).unwrap();
+        bronstein.record(secs(1)).unwrap();
+        assert_eq!(bronstein.remaining(Color::White), secs(53));
+        assert_eq!(bronstein.remaining(Color::Black), secs(60));
+
+        let mut simple = GameClock::sudden_death(secs(60)).bonus(TimeBonus::SimpleDelay(secs(3)));
+        simple.record(secs(10)).unwrap();
+        simple.record(secs(1)).unwrap();
+        assert_eq!(simple.remaining(Color::White), secs(53));
+        assert_eq!(simple.remaining(Color::Black), secs(60));
+
+        // Delays are not part of the UCI protocol.
+        assert_eq!(simple.go_params().to_string(), "wtime 53000 btime 60000");
+    }
+
+    #[test]
+    fn periods_test() {
+        let mut clock = GameClock::with_periods(vec![
+            TimePeriod {
+                moves: Some(2),
+                time: secs(100),
+            },
+            TimePeriod {
+                moves: None,
+                time: secs(30),
+            },
+        ]);
+        assert_eq!(
+            clock.go_params().to_string(),
+            "wtime 100000 btime 100000 movestogo 2"
+        );
+
+        clock.record(secs(10)).unwrap();
+        clock.record(secs(10)).unwrap();
+        assert_eq!(clock.moves_to_go(Color::White), Some(1));
+
+        clock.record(secs(10)).unwrap();
+        assert_eq!(clock.remaining(Color::White), secs(110));
+        assert_eq!(clock.moves_to_go(Color::White), None);
+        assert_eq!(clock.pgn_time_control(), "2/100:30");
+
+        let mut repeating = GameClock::with_periods(vec![TimePeriod {
+            moves: Some(1),
+            time: secs(10),
+        }]);
+        repeating.record(secs(4)).unwrap();
+        assert_eq!(repeating.remaining(Color::White), secs(16));
+        assert_eq!(repeating.moves_to_go(Color::White), Some(1));
+    }
+
+    #[test]
+    fn flag_fall_test() {
+        let mut clock = GameClock::sudden_death(secs(10))
+            .lag_tolerance(Duration::from_millis(500))
+            .first_to_move(Color::Black);
+
+        clock.record(Duration::from_millis(10_400)).unwrap();
+        assert_eq!(clock.remaining(Color::Black), secs(0));
+
+        clock.record(secs(1)).unwrap();
+        assert_eq!(
+            clock.record(Duration::from_millis(600)),
+            Err(FlagFall {
+                side: Color::Black,
+                used: Duration::from_millis(600),
+                remaining: secs(0),
+            })
+        );
+    }
+
+    #[test]
+    fn start_stop_test() {
+        let mut clock = GameClock::sudden_death(secs(60));
+        clock.start();
+        let elapsed = clock.stop().unwrap();
+
+        assert!(elapsed < secs(1));
+        assert_eq!(clock.remaining(Color::White), secs(60) - elapsed);
+        assert_eq!(clock.turn(), Color::Black);
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "board")]
+use board::BoardError;
+use fen::{Fen, FenError};
+use go_params::GoParamsError;
+use parsers::{Move, OptionError};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use timeouts::TimeoutKind;
+
+/// Everything that can go wrong while talking to an engine.
+#[derive(Debug)]
+pub enum UciError {
+    /// The engine process could not be started.
+    Spawn(io::Error),
+    /// Reading from or writing to the engine failed.
+    Io(io::Error),
+    /// The engine closed its stdout, which usually means it crashed.
+    UnexpectedEof,
+    /// The engine sent a line that could not be parsed.
+    Parse(String),
+    /// The engine sent a message that is not valid at this point of the
+    /// protocol.
+    Protocol(String),
+    /// The engine did not answer in time.
+    Timeout(TimeoutKind),
+    /// An option value was rejected before being sent to the engine.
+    Option(OptionError),
+    /// Search parameters were rejected before being sent to the engine.
+    GoParams(GoParamsError),
+    /// A position was rejected before being sent to the engine.
+    Fen(FenError),
+    /// A position was rejected before being sent to the engine, because one
+    /// of its moves is not legal.
+    Position { illegal_move: Move, fen: Fen },
+    /// The engine played a move that is not legal in the position.
+    IllegalMove { illegal_move: Move, fen: Fen },
+    /// Pondering was requested after a "bestmove" without a ponder move.
+    NoPonderMove,
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UciError::Spawn(ref e) => write!(f, "failed to spawn engine: {}", e),
+            UciError::Io(ref e) => write!(f, "failed to communicate with engine: {}", e),
+            UciError::UnexpectedEof => write!(f, "engine closed its output unexpectedly"),
+            UciError::Parse(ref line) => write!(f, "failed to parse engine output: {:?}", line),
+            UciError::Protocol(ref message) => write!(f, "protocol violation: {}", message),
+            UciError::Timeout(kind) => write!(f, "engine did not answer in time during {}", kind),
+            UciError::Option(ref e) => write!(f, "invalid option: {:?}", e),
+            UciError::GoParams(ref e) => write!(f, "invalid search parameters: {:?}", e),
+            UciError::Fen(ref e) => write!(f, "invalid FEN: {:?}", e),
+            UciError::Position {
+                ref illegal_move,
+                ref fen,
+            } => write!(f, "position with illegal move {} in {}", illegal_move, fen),
+            UciError::IllegalMove {
+                ref illegal_move,
+                ref fen,
+            } => write!(f, "illegal move {} in position {}", illegal_move, fen),
+            UciError::NoPonderMove => write!(f, "the engine did not suggest a move to ponder on"),
+        }
+    }
+}
+
+impl Error for UciError {
+    fn description(&self) -> &str {
+        match *self {
+            UciError::Spawn(_) => "failed to spawn engine",
+            UciError::Io(_) => "failed to communicate with engine",
+            UciError::UnexpectedEof => "engine closed its output unexpectedly",
+            UciError::Parse(_) => "failed to parse engine output",
+            UciError::Protocol(_) => "protocol violation",
+            UciError::Timeout(_) => "engine did not answer in time",
+            UciError::Option(_) => "invalid option",
+            UciError::GoParams(_) => "invalid search parameters",
+            UciError::Fen(_) => "invalid FEN",
+            UciError::Position { .. } => "position with an illegal move",
+            UciError::IllegalMove { .. } => "illegal move",
+            UciError::NoPonderMove => "no move to ponder on",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            UciError::Spawn(ref e) | UciError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for UciError {
+    fn from(e: io::Error) -> UciError {
+        UciError::Io(e)
+    }
+}
+
+impl From<OptionError> for UciError {
+    fn from(e: OptionError) -> UciError {
+        UciError::Option(e)
+    }
+}
+
+impl From<GoParamsError> for UciError {
+    fn from(e: GoParamsError) -> UciError {
+        UciError::GoParams(e)
+    }
+}
+
+impl From<FenError> for UciError {
+    fn from(e: FenError) -> UciError {
+        UciError::Fen(e)
+    }
+}
+
+/// A board error comes from a position given by the caller. Illegal moves
+/// played by the engine are reported by the search as `IllegalMove`.
+#[cfg(feature = "board")]
+impl From<BoardError> for UciError {
+    fn from(e: BoardError) -> UciError {
+        match e {
+            BoardError::InvalidFen(e) => UciError::Fen(e),
+            BoardError::IllegalMove { illegal_move, fen } => {
+                UciError::Position { illegal_move, fen }
+            }
+        }
+    }
+}
@@ -0,0 +1,680 @@
+use attacks::is_in_check;
+use parsers::{File, Move, Rank, Square};
+use std::fmt;
+use std::str::FromStr;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    /// The rank the color's pieces start on.
+    pub fn back_rank(self) -> Rank {
+        match self {
+            Color::White => Rank::First,
+            Color::Black => Rank::Eight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub color: Color,
+    pub kind: PieceKind,
+}
+
+impl Piece {
+    /// Reads a piece in FEN notation, where white pieces are uppercase.
+    pub fn from_char(c: char) -> Option<Piece> {
+        let kind = match c.to_ascii_lowercase() {
+            'p' => PieceKind::Pawn,
+            'n' => PieceKind::Knight,
+            'b' => PieceKind::Bishop,
+            'r' => PieceKind::Rook,
+            'q' => PieceKind::Queen,
+            'k' => PieceKind::King,
+            _ => return None,
+        };
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        Some(Piece { color, kind })
+    }
+
+    pub fn to_char(self) -> char {
+        let c = match self.kind {
+            PieceKind::Pawn => 'p',
+            PieceKind::Knight => 'n',
+            PieceKind::Bishop => 'b',
+            PieceKind::Rook => 'r',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+        };
+
+        match self.color {
+            Color::White => c.to_ascii_uppercase(),
+            Color::Black => c,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    KingSide,
+    QueenSide,
+}
+
+impl CastlingSide {
+    /// The file the king ends up on after castling.
+    pub fn king_destination(self) -> File {
+        match self {
+            CastlingSide::KingSide => File::G,
+            CastlingSide::QueenSide => File::C,
+        }
+    }
+
+    /// The file the rook ends up on after castling.
+    pub fn rook_destination(self) -> File {
+        match self {
+            CastlingSide::KingSide => File::F,
+            CastlingSide::QueenSide => File::D,
+        }
+    }
+}
+
+/// How castling moves are written in UCI.
+///
+/// Standard chess writes castling as a two-square king move, such as
+/// "e1g1". With `UCI_Chess960` enabled, engines write it as the king taking
+/// its own rook, such as "e1h1".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastlingMode {
+    Standard,
+    Chess960,
+}
+
+/// The rooks each side may still castle with, given by their file.
+///
+/// In standard chess these are always on the a-file and h-file, but in
+/// Chess960 they can start on any file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CastlingRights {
+    pub white_king_side: Option<File>,
+    pub white_queen_side: Option<File>,
+    pub black_king_side: Option<File>,
+    pub black_queen_side: Option<File>,
+}
+
+impl CastlingRights {
+    pub fn rook_file(&self, color: Color, side: CastlingSide) -> Option<File> {
+        match (color, side) {
+            (Color::White, CastlingSide::KingSide) => self.white_king_side,
+            (Color::White, CastlingSide::QueenSide) => self.white_queen_side,
+            (Color::Black, CastlingSide::KingSide) => self.black_king_side,
+            (Color::Black, CastlingSide::QueenSide) => self.black_queen_side,
+        }
+    }
+
+    pub fn set_rook_file(&mut self, color: Color, side: CastlingSide, file: Option<File>) {
+        let right = match (color, side) {
+            (Color::White, CastlingSide::KingSide) => &mut self.white_king_side,
+            (Color::White, CastlingSide::QueenSide) => &mut self.white_queen_side,
+            (Color::Black, CastlingSide::KingSide) => &mut self.black_king_side,
+            (Color::Black, CastlingSide::QueenSide) => &mut self.black_queen_side,
+        };
+        *right = file;
+    }
+}
+
+const CASTLING_SIDES: [CastlingSide; 2] = [CastlingSide::KingSide, CastlingSide::QueenSide];
+
+/// A position in Forsyth-Edwards Notation.
+///
+/// Parsing a FEN with `str::parse` validates it, so that malformed
+/// positions never reach the engine. The castling field can be written in
+/// X-FEN or Shredder-FEN for Chess960 positions, and is always displayed in
+/// X-FEN, which reads the same as standard FEN for standard positions.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::{Fen, STARTING_FEN};
+///
+/// let fen: Fen = STARTING_FEN.parse().unwrap();
+/// assert_eq!(fen.to_string(), STARTING_FEN);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fen {
+    /// The pieces on the board, indexed by rank then by file.
+    pub placement: [[Option<Piece>; 8]; 8],
+    pub side_to_move: Color,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenError {
+    WrongFieldCount,
+    InvalidPiecePlacement,
+    InvalidSideToMove,
+    InvalidCastling,
+    InvalidEnPassant,
+    InvalidHalfmoveClock,
+    InvalidFullmoveNumber,
+    /// Each side must have exactly one king.
+    InvalidKings,
+    PawnOnBackRank,
+    /// The side that just moved left its king in check.
+    OpponentInCheck,
+}
+
+impl Fen {
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.placement[square.rank.index()][square.file.index()]
+    }
+
+    /// Checks that the position is consistent, beyond the syntax of each
+    /// field.
+    pub fn validate(&self) -> Result<(), FenError> {
+        for &color in [Color::White, Color::Black].iter() {
+            let king = Piece {
+                color,
+                kind: PieceKind::King,
+            };
+            let kings = self.placement
+                .iter()
+                .flat_map(|rank| rank.iter())
+                .filter(|&&piece| piece == Some(king))
+                .count();
+            if kings != 1 {
+                return Err(FenError::InvalidKings);
+            }
+        }
+
+        for &rank in [Rank::First, Rank::Eight].iter() {
+            let has_pawn = self.placement[rank.index()]
+                .iter()
+                .any(|piece| piece.map(|p| p.kind) == Some(PieceKind::Pawn));
+            if has_pawn {
+                return Err(FenError::PawnOnBackRank);
+            }
+        }
+
+        if is_in_check(self, self.side_to_move.opposite()) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        self.validate_castling()?;
+        self.validate_en_passant()?;
+
+        if self.fullmove_number == 0 {
+            return Err(FenError::InvalidFullmoveNumber);
+        }
+
+        Ok(())
+    }
+
+    fn has(&self, file: File, rank: Rank, color: Color, kind: PieceKind) -> bool {
+        self.piece_at(Square { file, rank }) == Some(Piece { color, kind })
+    }
+
+    /// The file of the color's king, if it stands on its back rank.
+    fn back_rank_king(&self, color: Color) -> Option<File> {
+        File::ALL
+            .iter()
+            .cloned()
+            .find(|&file| self.has(file, color.back_rank(), color, PieceKind::King))
+    }
+
+    /// The rook furthest from the king on the given side, which is the one
+    /// "K" and "Q" refer to in X-FEN.
+    fn outermost_rook(&self, color: Color, side: CastlingSide) -> Option<File> {
+        let king = self.back_rank_king(color)?.index();
+        let is_rook = |&file: &File| self.has(file, color.back_rank(), color, PieceKind::Rook);
+
+        match side {
+            CastlingSide::KingSide => File::ALL[king + 1..].iter().rev().cloned().find(is_rook),
+            CastlingSide::QueenSide => File::ALL[..king].iter().cloned().find(is_rook),
+        }
+    }
+
+    /// The move that castles on `side` for the side to move, written the way
+    /// `mode` expects, or `None` if castling on that side is not allowed.
+    ///
+    /// Standard mode falls back to the king taking its rook when the king
+    /// and rook are not on their usual squares, since the two-square king
+    /// move can be ambiguous there.
+    pub fn castling_move(&self, side: CastlingSide, mode: CastlingMode) -> Option<Move> {
+        let color = self.side_to_move;
+        let rank = color.back_rank();
+        let rook = self.castling.rook_file(color, side)?;
+        let king = self.back_rank_king(color)?;
+        let usual_rook = match side {
+            CastlingSide::KingSide => File::H,
+            CastlingSide::QueenSide => File::A,
+        };
+
+        let to = if mode == CastlingMode::Standard && king == File::E && rook == usual_rook {
+            side.king_destination()
+        } else {
+            rook
+        };
+
+        Some(Move {
+            from: Square { file: king, rank },
+            to: Square { file: to, rank },
+            promotion_piece: None,
+        })
+    }
+
+    fn validate_castling(&self) -> Result<(), FenError> {
+        for &color in [Color::White, Color::Black].iter() {
+            for &side in CASTLING_SIDES.iter() {
+                let rook = match self.castling.rook_file(color, side) {
+                    Some(rook) => rook,
+                    None => continue,
+                };
+                let king = self.back_rank_king(color).ok_or(FenError::InvalidCastling)?;
+                let on_side = match side {
+                    CastlingSide::KingSide => rook.index() > king.index(),
+                    CastlingSide::QueenSide => rook.index() < king.index(),
+                };
+
+                if !on_side || !self.has(rook, color.back_rank(), color, PieceKind::Rook) {
+                    return Err(FenError::InvalidCastling);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_en_passant(&self) -> Result<(), FenError> {
+        let square = match self.en_passant {
+            Some(square) => square,
+            None => return Ok(()),
+        };
+
+        // The pawn that just moved two squares stands in front of the en
+        // passant square, and the square it came from is now empty.
+        let (target_rank, pawn_rank, origin_rank) = match self.side_to_move {
+            Color::White => (Rank::Sixth, Rank::Fifth, Rank::Seventh),
+            Color::Black => (Rank::Third, Rank::Fourth, Rank::Second),
+        };
+        let mover = self.side_to_move.opposite();
+
+        if square.rank != target_rank
+            || self.piece_at(square).is_some()
+            || self.piece_at(Square {
+                file: square.file,
+                rank: origin_rank,
+            }).is_some()
+            || !self.has(square.file, pawn_rank, mover, PieceKind::Pawn)
+        {
+            return Err(FenError::InvalidEnPassant);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_placement(field: &str) -> Result<[[Option<Piece>; 8]; 8], FenError> {
+    let mut placement = [[None; 8]; 8];
+    let rows: Vec<&str> = field.split('/').collect();
+    if rows.len() != 8 {
+        return Err(FenError::InvalidPiecePlacement);
+    }
+
+    // The first row is the eighth rank.
+    for (pieces, row) in placement.iter_mut().rev().zip(rows) {
+        let mut file = 0;
+
+        for c in row.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                if empty == 0 || empty > 8 {
+                    return Err(FenError::InvalidPiecePlacement);
+                }
+                file += empty as usize;
+            } else {
+                let piece = Piece::from_char(c).ok_or(FenError::InvalidPiecePlacement)?;
+                if file >= 8 {
+                    return Err(FenError::InvalidPiecePlacement);
+                }
+                pieces[file] = Some(piece);
+                file += 1;
+            }
+        }
+
+        if file != 8 {
+            return Err(FenError::InvalidPiecePlacement);
+        }
+    }
+
+    Ok(placement)
+}
+
+/// Reads the castling field in standard FEN, X-FEN or Shredder-FEN. Rooks
+/// are looked up in the already parsed placement.
+fn parse_castling(field: &str, fen: &Fen) -> Result<CastlingRights, FenError> {
+    let mut castling = CastlingRights::default();
+    if field == "-" {
+        return Ok(castling);
+    }
+
+    for c in field.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let king = fen.back_rank_king(color).ok_or(FenError::InvalidCastling)?;
+
+        let (side, rook) = match c.to_ascii_lowercase() {
+            'k' => (
+                CastlingSide::KingSide,
+                fen.outermost_rook(color, CastlingSide::KingSide),
+            ),
+            'q' => (
+                CastlingSide::QueenSide,
+                fen.outermost_rook(color, CastlingSide::QueenSide),
+            ),
+            file if file >= 'a' && file <= 'h' => {
+                let file = File::from_index((file as u8 - b'a') as usize);
+                let side = match file {
+                    Some(file) if file.index() > king.index() => CastlingSide::KingSide,
+                    _ => CastlingSide::QueenSide,
+                };
+                (side, file)
+            }
+            _ => return Err(FenError::InvalidCastling),
+        };
+
+        let rook = rook.ok_or(FenError::InvalidCastling)?;
+        if castling.rook_file(color, side).is_some() {
+            return Err(FenError::InvalidCastling);
+        }
+        castling.set_rook_file(color, side, Some(rook));
+    }
+
+    Ok(castling)
+}
+
+fn parse_en_passant(field: &str) -> Result<Option<Square>, FenError> {
+    if field == "-" {
+        return Ok(None);
+    }
+
+    let bytes = field.as_bytes();
+    if bytes.len() != 2 || bytes[0] < b'a' || bytes[1] < b'1' {
+        return Err(FenError::InvalidEnPassant);
+    }
+
+    let file = File::from_index((bytes[0] - b'a') as usize);
+    let rank = Rank::from_index((bytes[1] - b'1') as usize);
+    match (file, rank) {
+        (Some(file), Some(rank)) => Ok(Some(Square { file, rank })),
+        _ => Err(FenError::InvalidEnPassant),
+    }
+}
+
+impl FromStr for Fen {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<Fen, FenError> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount);
+        }
+
+        let mut fen = Fen {
+            placement: parse_placement(fields[0])?,
+            side_to_move: match fields[1] {
+                "w" => Color::White,
+                "b" => Color::Black,
+                _ => return Err(FenError::InvalidSideToMove),
+            },
+            castling: CastlingRights::default(),
+            en_passant: parse_en_passant(fields[3])?,
+            halfmove_clock: fields[4]
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock)?,
+            fullmove_number: fields[5]
+                .parse()
+                .map_err(|_| FenError::InvalidFullmoveNumber)?,
+        };
+        fen.castling = parse_castling(fields[2], &fen)?;
+
+        fen.validate()?;
+        Ok(fen)
+    }
+}
+
+impl fmt::Display for Fen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.placement.iter().rev().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+
+            let mut empty = 0;
+            for square in row.iter() {
+                match *square {
+                    Some(piece) => {
+                        if empty > 0 {
+                            write!(f, "{}", empty)?;
+                            empty = 0;
+                        }
+                        write!(f, "{}", piece.to_char())?;
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                write!(f, "{}", empty)?;
+            }
+        }
+
+        match self.side_to_move {
+            Color::White => write!(f, " w ")?,
+            Color::Black => write!(f, " b ")?,
+        }
+
+        let mut castling = String::new();
+        for &color in [Color::White, Color::Black].iter() {
+            for &side in CASTLING_SIDES.iter() {
+                let rook = match self.castling.rook_file(color, side) {
+                    Some(rook) => rook,
+                    None => continue,
+                };
+
+                // X-FEN only names the file when "K" or "Q" would refer to
+                // another rook.
+                let c = if self.outermost_rook(color, side) == Some(rook) {
+                    match side {
+                        CastlingSide::KingSide => 'k',
+                        CastlingSide::QueenSide => 'q',
+                    }
+                } else {
+                    (b'a' + rook.index() as u8) as char
+                };
+                castling.push(match color {
+                    Color::White => c.to_ascii_uppercase(),
+                    Color::Black => c,
+                });
+            }
+        }
+        if castling.is_empty() {
+            write!(f, "-")?;
+        } else {
+            write!(f, "{}", castling)?;
+        }
+
+        match self.en_passant {
+            Some(square) => write!(f, " {}", square)?,
+            None => write!(f, " -")?,
+        }
+
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let fens = [
+            STARTING_FEN,
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 12 40",
+            "8/8/8/8/4p3/8/3P4/k6K w - - 0 1",
+        ];
+
+        for fen in fens.iter() {
+            assert_eq!(fen.parse::<Fen>().unwrap().to_string(), *fen);
+        }
+    }
+
+    #[test]
+    fn fields_test() {
+        let fen: Fen = "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b Qk d3 0 3"
+            .parse()
+            .unwrap();
+
+        assert_eq!(fen.side_to_move, Color::Black);
+        assert_eq!(
+            fen.castling,
+            CastlingRights {
+                white_king_side: None,
+                white_queen_side: Some(File::A),
+                black_king_side: Some(File::H),
+                black_queen_side: None,
+            }
+        );
+        assert_eq!(
+            fen.en_passant,
+            Some(Square {
+                file: File::D,
+                rank: Rank::Third,
+            })
+        );
+        assert_eq!(
+            fen.piece_at(Square {
+                file: File::E,
+                rank: Rank::Fourth,
+            }),
+            Some(Piece {
+                color: Color::Black,
+                kind: PieceKind::Pawn,
+            })
+        );
+        assert_eq!(fen.halfmove_clock, 0);
+        assert_eq!(fen.fullmove_number, 3);
+    }
+
+    #[test]
+    fn chess960_castling_test() {
+        let shredder: Fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            shredder.castling,
+            CastlingRights {
+                white_king_side: Some(File::H),
+                white_queen_side: Some(File::F),
+                black_king_side: Some(File::H),
+                black_queen_side: Some(File::F),
+            }
+        );
+        assert_eq!(
+            shredder.to_string(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+
+        // With two rooks on the same side, X-FEN names the inner one by file.
+        let xfen: Fen = "4k3/8/8/8/8/8/8/R3K1RR w GQ - 0 1".parse().unwrap();
+        assert_eq!(xfen.castling.white_king_side, Some(File::G));
+        assert_eq!(xfen.castling.white_queen_side, Some(File::A));
+        assert_eq!(xfen.to_string(), "4k3/8/8/8/8/8/8/R3K1RR w GQ - 0 1");
+
+        let outer: Fen = "4k3/8/8/8/8/8/8/R3K1RR w K - 0 1".parse().unwrap();
+        assert_eq!(outer.castling.white_king_side, Some(File::H));
+    }
+
+    #[test]
+    fn castling_move_test() {
+        let standard: Fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+        let chess960: Fen = "1r4kr/8/8/8/8/8/8/1R4KR b KQkq - 0 1".parse().unwrap();
+        let castling = |fen: &Fen, side, mode| fen.castling_move(side, mode).unwrap().to_string();
+
+        assert_eq!(castling(&standard, CastlingSide::KingSide, CastlingMode::Standard), "e1g1");
+        assert_eq!(castling(&standard, CastlingSide::QueenSide, CastlingMode::Standard), "e1c1");
+        assert_eq!(castling(&standard, CastlingSide::KingSide, CastlingMode::Chess960), "e1h1");
+        assert_eq!(castling(&chess960, CastlingSide::KingSide, CastlingMode::Standard), "g8h8");
+        assert_eq!(castling(&chess960, CastlingSide::QueenSide, CastlingMode::Chess960), "g8b8");
+    }
+
+    #[test]
+    fn syntax_error_test() {
+        let errors = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", FenError::WrongFieldCount),
+            ("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPiecePlacement),
+            ("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPiecePlacement),
+            ("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPiecePlacement),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::InvalidSideToMove),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkK - 0 1", FenError::InvalidCastling),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1", FenError::InvalidEnPassant),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", FenError::InvalidHalfmoveClock),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 -1", FenError::InvalidFullmoveNumber),
+        ];
+
+        for &(fen, error) in errors.iter() {
+            assert_eq!(fen.parse::<Fen>(), Err(error), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn validation_error_test() {
+        let errors = [
+            ("rnbqqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1", FenError::InvalidKings),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKKBNR w - - 0 1", FenError::InvalidKings),
+            ("rnbqkbnP/pppppppp/8/8/8/8/PPPPPPP1/RNBQKBNR w - - 0 1", FenError::PawnOnBackRank),
+            ("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1", FenError::OpponentInCheck),
+            ("4k3/8/8/8/8/8/8/r3K3 b - - 0 1", FenError::OpponentInCheck),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1", FenError::InvalidCastling),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1", FenError::InvalidEnPassant),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0", FenError::InvalidFullmoveNumber),
+        ];
+
+        for &(fen, error) in errors.iter() {
+            assert_eq!(fen.parse::<Fen>(), Err(error), "{}", fen);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use board::Outcome;
+use clock::GameClock;
+use error::UciError;
+use fen::Color;
+use futures::prelude::*;
+use go_params::GoParams;
+use parsers::{BestMove, Score};
+use pgn::{GameResult, PgnGame};
+use position::Position;
+use timeouts::TimeoutKind;
+use Engine;
+
+/// Why a game played with `play_game` ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The game ended on the board.
+    Outcome(Outcome),
+    /// The side ran out of time, or did not answer in time at all.
+    TimeForfeit { loser: Color },
+    /// The side played an illegal move, or no move in a position with legal
+    /// moves.
+    IllegalMove { loser: Color },
+    /// The side's engine crashed, hung or broke the protocol.
+    Crash { loser: Color },
+}
+
+impl Termination {
+    pub fn result(&self) -> GameResult {
+        match *self {
+            Termination::Outcome(outcome) => outcome.into(),
+            Termination::TimeForfeit { loser }
+            | Termination::IllegalMove { loser }
+            | Termination::Crash { loser } => match loser {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            },
+        }
+    }
+
+    /// The value of the PGN "Termination" tag.
+    pub fn pgn_tag(&self) -> &'static str {
+        match *self {
+            Termination::Outcome(_) => "normal",
+            Termination::TimeForfeit { .. } => "time forfeit",
+            Termination::IllegalMove { .. } => "rules infraction",
+            Termination::Crash { .. } => "abandoned",
+        }
+    }
+}
+
+/// A game played with `play_game`.
+pub struct GameRecord {
+    /// The moves, with the engines' evaluations, remaining times and time
+    /// spent, and the result.
+    pub game: PgnGame,
+    pub termination: Termination,
+    /// What went wrong with the engine that lost, if it failed.
+    pub error: Option<UciError>,
+    /// The engines, ready for another game, unless they failed.
+    pub white: Option<Engine>,
+    pub black: Option<Engine>,
+}
+
+fn failure(loser: Color, error: &UciError) -> Termination {
+    match *error {
+        UciError::Timeout(TimeoutKind::Search) => Termination::TimeForfeit { loser },
+        UciError::IllegalMove { .. } => Termination::IllegalMove { loser },
+        _ => Termination::Crash { loser },
+    }
+}
+
+/// Records how the game ended in its result and "Termination" tag.
+pub(crate) fn finish(game: &mut PgnGame, termination: Termination) {
+    game.set_result(termination.result());
+    game.set_tag("Termination", termination.pgn_tag());
+}
+
+fn index(side: Color) -> usize {
+    match side {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// Searches the position already sent to the engine, and keeps the last
+/// score it reported along with its move.
+#[async]
+fn think(
+    engine: Engine,
+    params: GoParams,
+) -> Result<(Engine, BestMove, Option<Score>), UciError> {
+    let mut search = await!(engine.go(params))?;
+    let mut score = None;
+
+    loop {
+        let (info, rest) = await!(search.into_future()).map_err(|(e, _)| e)?;
+        search = rest;
+
+        match info {
+            Some(info) => {
+                if info.score.is_some() && info.multipv.map_or(true, |n| n == 1) {
+                    score = info.score;
+                }
+            }
+            None => break,
+        }
+    }
+
+    let (engine, best_move) = await!(search.wait())?;
+    Ok((engine, best_move, score))
+}
+
+/// Plays a game between two engines from `opening`, with `clock` for both
+/// sides.
+///
+/// The moves are checked and the game is adjudicated: it ends on the board,
+/// or with a loss for the engine that runs out of time, plays an illegal
+/// move or fails. Castling moves are read in the white engine's castling
+/// mode, which both engines should share. Only an invalid `opening` makes
+/// the future fail.
+///
+/// The clock only runs out once an engine answers, so an engine that hangs
+/// stalls the game unless it was started with `Timeouts` that set a search
+/// grace period. The search then times out shortly after the engine's
+/// remaining time, and the game is lost on time.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// extern crate async_uci;
+/// extern crate futures;
+/// extern crate tokio_core;
+///
+/// use async_uci::{play_game, Engine, GameClock, Position, Timeouts};
+/// use futures::Future;
+/// use std::time::Duration;
+/// use tokio_core::reactor::Core;
+///
+/// fn main() {
+///     let mut core = Core::new().unwrap();
+///     let handle = core.handle();
+///     let timeouts = Timeouts::new(&handle)
+///         .handshake(Duration::from_secs(10))
+///         .sync(Duration::from_secs(10))
+///         .search_grace(Duration::from_secs(1));
+///     let spawn = |path: &str| {
+///         Engine::from_path_with_timeouts(path.to_string(), &handle, timeouts.clone())
+///     };
+///     let game = spawn("stockfish")
+///         .join(spawn("ethereal"))
+///         .and_then(|(white, black)| {
+///             let clock = GameClock::sudden_death(Duration::from_secs(60));
+///             play_game(white, black, Position::startpos(), clock)
+///         });
+///
+///     let record = core.run(game).unwrap();
+///     println!("{}", record.game);
+/// }
+/// ```
+#[async]
+pub fn play_game(
+    white: Engine,
+    black: Engine,
+    opening: Position,
+    clock: GameClock,
+) -> Result<GameRecord, UciError> {
+    let mut game = PgnGame::from_position(&opening, white.castling_mode())?;
+    game.set_players(white.id(), black.id());
+    game.set_time_control(&clock.pgn_time_control());
+
+    let mut clock = clock.first_to_move(opening.side_to_move());
+    let mut position = opening;
+    let mut engines = [Some(white), Some(black)];
+
+    let mut ended = None;
+    for side in vec![Color::White, Color::Black] {
+        let engine = engines[index(side)].take().unwrap();
+        match await!(engine.new_game()) {
+            Ok(engine) => engines[index(side)] = Some(engine),
+            Err(e) => {
+                ended = Some((failure(side, &e), Some(e)));
+                break;
+            }
+        }
+    }
+
+    let (termination, error) = match ended {
+        Some(ended) => ended,
+        None => loop {
+            if let Some(outcome) = game.board().outcome() {
+                break (Termination::Outcome(outcome), None);
+            }
+
+            let side = game.board().side_to_move();
+            let engine = engines[index(side)].take().unwrap();
+            let engine = match await!(engine.set_position(position.clone())) {
+                Ok(engine) => engine,
+                Err(e) => break (failure(side, &e), Some(e)),
+            };
+
+            clock.start();
+            let (engine, best_move, score) = match await!(think(engine, clock.go_params())) {
+                Ok(searched) => searched,
+                Err(e) => break (failure(side, &e), Some(e)),
+            };
+            engines[index(side)] = Some(engine);
+
+            let elapsed = match clock.stop() {
+                Ok(elapsed) => elapsed,
+                Err(_) => break (Termination::TimeForfeit { loser: side }, None),
+            };
+
+            let m = match best_move.best_move {
+                Some(m) if game.board().is_legal(m) => m,
+                _ => break (Termination::IllegalMove { loser: side }, None),
+            };
+            game.push_annotated(m, score, Some(clock.remaining(side)), Some(elapsed))?;
+            position.push(m);
+        },
+    };
+
+    finish(&mut game, termination);
+
+    Ok(GameRecord {
+        game,
+        termination,
+        error,
+        white: engines[0].take(),
+        black: engines[1].take(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fen::Fen;
+    use std::time::Duration;
+    use tests::fake_engine;
+    use tokio_core::reactor::Core;
+
+    #[test]
+    fn termination_test() {
+        let mate = Termination::Outcome(Outcome::Checkmate {
+            winner: Color::Black,
+        });
+        assert_eq!(mate.result(), GameResult::BlackWins);
+        assert_eq!(mate.pgn_tag(), "normal");
+
+        let stalemate = Termination::Outcome(Outcome::Stalemate);
+        assert_eq!(stalemate.result(), GameResult::Draw);
+
+        let flag = Termination::TimeForfeit { loser: Color::Black };
+        assert_eq!(flag.result(), GameResult::WhiteWins);
+        assert_eq!(flag.pgn_tag(), "time forfeit");
+
+        let crash = Termination::Crash { loser: Color::White };
+        assert_eq!(crash.result(), GameResult::BlackWins);
+        assert_eq!(
+            failure(Color::White, &UciError::Timeout(TimeoutKind::Search)),
+            Termination::TimeForfeit { loser: Color::White }
+        );
+        assert_eq!(failure(Color::White, &UciError::UnexpectedEof), crash);
+    }
+
+    /// An engine that answers every "go" by running `answer`.
+    fn scripted(core: &mut Core, answer: &str) -> Engine {
+        let script = format!(
+            "while read command rest; do case $command in \
+             uci) echo uciok;; isready) echo readyok;; go) {};; quit) exit;; \
+             esac; done",
+            answer
+        );
+        let process = fake_engine(&core.handle(), &script);
+        core.run(Engine::new(process)).unwrap()
+    }
+
+    fn mate_in_one() -> Position {
+        let fen: Fen = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1".parse().unwrap();
+        Position::fen(fen)
+    }
+
+    fn clock() -> GameClock {
+        GameClock::sudden_death(Duration::from_secs(10))
+    }
+
+    #[test]
+    fn game_test() {
+        let mut core = Core::new().unwrap();
+        let white = scripted(&mut core, "echo 'info depth 1 score mate 1'; echo 'bestmove d1d8'");
+        let black = scripted(&mut core, "echo 'bestmove (none)'");
+
+        let record = core.run(play_game(white, black, mate_in_one(), clock()))
+            .unwrap();
+
+        assert_eq!(
+            record.termination,
+            Termination::Outcome(Outcome::Checkmate {
+                winner: Color::White,
+            })
+        );
+        assert_eq!(record.game.result(), GameResult::WhiteWins);
+        assert_eq!(record.game.moves()[0].san, "Rd8#");
+        assert_eq!(record.game.tag("Termination"), Some("normal"));
+        assert!(record.error.is_none());
+        assert!(record.white.is_some() && record.black.is_some());
+    }
+
+    #[test]
+    fn crash_test() {
+        let mut core = Core::new().unwrap();
+        let white = scripted(&mut core, "exit 1");
+        let black = scripted(&mut core, "echo 'bestmove (none)'");
+
+        let record = core.run(play_game(white, black, mate_in_one(), clock()))
+            .unwrap();
+
+        assert_eq!(record.termination, Termination::Crash { loser: Color::White });
+        assert_eq!(record.game.result(), GameResult::BlackWins);
+        assert_eq!(record.game.tag("Termination"), Some("abandoned"));
+        match record.error {
+            Some(UciError::UnexpectedEof) => {}
+            ref other => panic!("unexpected error {:?}", other),
+        }
+        assert!(record.white.is_none() && record.black.is_some());
+    }
+
+    #[test]
+    fn illegal_move_test() {
+        let mut core = Core::new().unwrap();
+        let white = scripted(&mut core, "echo 'bestmove a1a8'");
+        let black = scripted(&mut core, "echo 'bestmove (none)'");
+
+        let record = core.run(play_game(white, black, mate_in_one(), clock()))
+            .unwrap();
+
+        assert_eq!(
+            record.termination,
+            Termination::IllegalMove { loser: Color::White }
+        );
+        assert_eq!(record.game.result(), GameResult::BlackWins);
+        assert_eq!(record.game.tag("Termination"), Some("rules infraction"));
+        assert!(record.game.moves().is_empty());
+    }
+}
@@ -0,0 +1,309 @@
+use fen::Color;
+use parsers::Move;
+use std::fmt;
+use std::time::Duration;
+
+/// The parameters of a "go" command.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::GoParams;
+/// use std::time::Duration;
+///
+/// let params = GoParams::new()
+///     .wtime(Duration::from_secs(300))
+///     .btime(Duration::from_secs(300))
+///     .winc(Duration::from_secs(2))
+///     .binc(Duration::from_secs(2));
+///
+/// assert_eq!(
+///     params.to_string(),
+///     "wtime 300000 btime 300000 winc 2000 binc 2000"
+/// );
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GoParams {
+    searchmoves: Vec<Move>,
+    ponder: bool,
+    wtime: Option<Duration>,
+    btime: Option<Duration>,
+    winc: Option<Duration>,
+    binc: Option<Duration>,
+    movestogo: Option<u32>,
+    depth: Option<u32>,
+    nodes: Option<u64>,
+    mate: Option<u32>,
+    movetime: Option<Duration>,
+    infinite: bool,
+}
+
+/// A combination of parameters that makes no sense to send to an engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoParamsError {
+    /// `infinite` was combined with a limit on the search.
+    InfiniteWithLimit,
+    /// An increment was given without the matching remaining time.
+    IncrementWithoutTime,
+    /// `movestogo` was given without any remaining time.
+    MovesToGoWithoutTime,
+    /// `movestogo` was zero.
+    ZeroMovesToGo,
+}
+
+impl GoParams {
+    pub fn new() -> GoParams {
+        GoParams::default()
+    }
+
+    /// Restricts the search to these moves.
+    pub fn searchmoves(mut self, moves: Vec<Move>) -> GoParams {
+        self.searchmoves = moves;
+        self
+    }
+
+    pub fn ponder(mut self) -> GoParams {
+        self.ponder = true;
+        self
+    }
+
+    pub fn wtime(mut self, time: Duration) -> GoParams {
+        self.wtime = Some(time);
+        self
+    }
+
+    pub fn btime(mut self, time: Duration) -> GoParams {
+        self.btime = Some(time);
+        self
+    }
+
+    pub fn winc(mut self, increment: Duration) -> GoParams {
+        self.winc = Some(increment);
+        self
+    }
+
+    pub fn binc(mut self, increment: Duration) -> GoParams {
+        self.binc = Some(increment);
+        self
+    }
+
+    pub fn movestogo(mut self, moves: u32) -> GoParams {
+        self.movestogo = Some(moves);
+        self
+    }
+
+    pub fn depth(mut self, depth: u32) -> GoParams {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn nodes(mut self, nodes: u64) -> GoParams {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    /// Searches for a mate in the given number of moves.
+    pub fn mate(mut self, moves: u32) -> GoParams {
+        self.mate = Some(moves);
+        self
+    }
+
+    pub fn movetime(mut self, time: Duration) -> GoParams {
+        self.movetime = Some(time);
+        self
+    }
+
+    pub fn infinite(mut self) -> GoParams {
+        self.infinite = true;
+        self
+    }
+
+    /// The longest the search should take when `side` is to move: its
+    /// "movetime", or else the side's remaining time. Pondering and infinite
+    /// searches have no limit until they are told to stop.
+    pub fn time_limit(&self, side: Color) -> Option<Duration> {
+        if self.ponder || self.infinite {
+            return None;
+        }
+
+        let remaining = match side {
+            Color::White => self.wtime,
+            Color::Black => self.btime,
+        };
+        self.movetime.or(remaining)
+    }
+
+    /// Checks that the parameters do not contradict each other.
+    pub fn validate(&self) -> Result<(), GoParamsError> {
+        let has_clock = self.wtime.is_some() || self.btime.is_some();
+        let has_limit = has_clock || self.movetime.is_some() || self.depth.is_some()
+            || self.nodes.is_some() || self.mate.is_some();
+
+        if self.infinite && has_limit {
+            Err(GoParamsError::InfiniteWithLimit)
+        } else if (self.winc.is_some() && self.wtime.is_none())
+            || (self.binc.is_some() && self.btime.is_none())
+        {
+            Err(GoParamsError::IncrementWithoutTime)
+        } else if self.movestogo.is_some() && !has_clock {
+            Err(GoParamsError::MovesToGoWithoutTime)
+        } else if self.movestogo == Some(0) {
+            Err(GoParamsError::ZeroMovesToGo)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos() / 1_000_000)
+}
+
+impl fmt::Display for GoParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tokens = Vec::new();
+
+        if self.ponder {
+            tokens.push("ponder".to_string());
+        }
+
+        let times = [
+            ("wtime", self.wtime),
+            ("btime", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+        ];
+        for &(name, time) in times.iter() {
+            if let Some(time) = time {
+                tokens.push(format!("{} {}", name, millis(time)));
+            }
+        }
+
+        if let Some(moves) = self.movestogo {
+            tokens.push(format!("movestogo {}", moves));
+        }
+        if let Some(depth) = self.depth {
+            tokens.push(format!("depth {}", depth));
+        }
+        if let Some(nodes) = self.nodes {
+            tokens.push(format!("nodes {}", nodes));
+        }
+        if let Some(moves) = self.mate {
+            tokens.push(format!("mate {}", moves));
+        }
+        if let Some(time) = self.movetime {
+            tokens.push(format!("movetime {}", millis(time)));
+        }
+        if self.infinite {
+            tokens.push("infinite".to_string());
+        }
+
+        // Some engines treat every token after "searchmoves" as a move, so
+        // it has to come last.
+        if !self.searchmoves.is_empty() {
+            let moves: Vec<String> = self.searchmoves.iter().map(|m| m.to_string()).collect();
+            tokens.push(format!("searchmoves {}", moves.join(" ")));
+        }
+
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsers::{File, Rank, Square};
+
+    #[test]
+    fn display_test() {
+        let e2e4 = Move {
+            from: Square {
+                file: File::E,
+                rank: Rank::Second,
+            },
+            to: Square {
+                file: File::E,
+                rank: Rank::Fourth,
+            },
+            promotion_piece: None,
+        };
+
+        assert_eq!(GoParams::new().to_string(), "");
+        assert_eq!(GoParams::new().infinite().to_string(), "infinite");
+        assert_eq!(
+            GoParams::new()
+                .wtime(Duration::from_millis(60500))
+                .btime(Duration::from_secs(59))
+                .movestogo(20)
+                .to_string(),
+            "wtime 60500 btime 59000 movestogo 20"
+        );
+        assert_eq!(
+            GoParams::new()
+                .searchmoves(vec![e2e4])
+                .depth(12)
+                .nodes(100000)
+                .to_string(),
+            "depth 12 nodes 100000 searchmoves e2e4"
+        );
+        assert_eq!(
+            GoParams::new()
+                .ponder()
+                .movetime(Duration::from_millis(1500))
+                .to_string(),
+            "ponder movetime 1500"
+        );
+    }
+
+    #[test]
+    fn time_limit_test() {
+        let clock = GoParams::new()
+            .wtime(Duration::from_secs(60))
+            .btime(Duration::from_secs(30));
+        assert_eq!(clock.time_limit(Color::White), Some(Duration::from_secs(60)));
+        assert_eq!(clock.time_limit(Color::Black), Some(Duration::from_secs(30)));
+
+        let movetime = GoParams::new()
+            .wtime(Duration::from_secs(60))
+            .movetime(Duration::from_secs(2));
+        assert_eq!(movetime.time_limit(Color::White), Some(Duration::from_secs(2)));
+
+        assert_eq!(GoParams::new().depth(20).time_limit(Color::White), None);
+        assert_eq!(GoParams::new().infinite().time_limit(Color::White), None);
+        assert_eq!(clock.ponder().time_limit(Color::White), None);
+    }
+
+    #[test]
+    fn validate_test() {
+        assert_eq!(GoParams::new().infinite().validate(), Ok(()));
+        assert_eq!(
+            GoParams::new()
+                .wtime(Duration::from_secs(10))
+                .winc(Duration::from_secs(1))
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            GoParams::new()
+                .infinite()
+                .movetime(Duration::from_secs(1))
+                .validate(),
+            Err(GoParamsError::InfiniteWithLimit)
+        );
+        assert_eq!(
+            GoParams::new().binc(Duration::from_secs(1)).validate(),
+            Err(GoParamsError::IncrementWithoutTime)
+        );
+        assert_eq!(
+            GoParams::new().movestogo(10).validate(),
+            Err(GoParamsError::MovesToGoWithoutTime)
+        );
+        assert_eq!(
+            GoParams::new()
+                .btime(Duration::from_secs(10))
+                .movestogo(0)
+                .validate(),
+            Err(GoParamsError::ZeroMovesToGo)
+        );
+    }
+}
@@ -7,75 +7,301 @@ extern crate tokio_core;
 extern crate tokio_io;
 extern crate tokio_process;
 
+mod attacks;
+#[cfg(feature = "board")]
+mod board;
+mod clock;
+mod error;
+mod fen;
+#[cfg(feature = "board")]
+mod game_match;
+mod go_params;
+mod log;
 mod parsers;
-
+#[cfg(feature = "board")]
+mod pgn;
+mod position;
+#[cfg(feature = "board")]
+mod san;
+mod search;
+mod timeouts;
+#[cfg(feature = "board")]
+mod tournament;
+
+use futures::future;
 use futures::prelude::*;
 use futures::{Future, Stream};
 use nom::types::CompleteStr;
-use parsers::{engine_message, EngineMessage};
-use std::io::BufReader;
+use parsers::{check_line_break, engine_message, EngineMessage, IdField};
+use std::collections::HashMap;
+use std::io::{self, BufReader};
 use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+use timeouts::Deadline;
 use tokio_core::reactor::Handle;
 use tokio_io::io::{lines, write_all, Lines};
 use tokio_process::{Child, ChildStdin, ChildStdout, CommandExt};
 
-pub use parsers::{BestMove, File, Move, PromotionPiece, Rank};
+#[cfg(feature = "board")]
+pub use board::{Board, BoardError, Outcome};
+pub use clock::{FlagFall, GameClock, TimeBonus, TimePeriod};
+pub use error::UciError;
+pub use fen::{
+    CastlingMode, CastlingRights, CastlingSide, Color, Fen, FenError, Piece, PieceKind,
+    STARTING_FEN,
+};
+#[cfg(feature = "board")]
+pub use game_match::{play_game, GameRecord, Termination};
+pub use go_params::{GoParams, GoParamsError};
+pub use log::{Direction, ProtocolLogger, SilentLogger, StderrLogger, TranscriptLogger};
+pub use parsers::{
+    BestMove, Bound, CurrLine, File, Move, OptionError, ParseMoveError, PromotionPiece, Rank,
+    Score, SearchInfo, Square, UciOption, UciOptionType, UciOptionValue,
+};
+#[cfg(feature = "board")]
+pub use pgn::{GameResult, PgnError, PgnGame, PgnMove};
+pub use position::Position;
+#[cfg(feature = "board")]
+pub use san::SanError;
+pub use search::{PonderHandle, SearchHandle};
+pub use timeouts::{TimeoutKind, Timeouts};
+#[cfg(feature = "board")]
+pub use tournament::{
+    Crosstable, Format, Pairing, Tally, Tournament, TournamentGame, TournamentResult,
+};
+
+const CHESS960_OPTION: &str = "UCI_Chess960";
 
 pub struct Engine {
-    process: Child,
+    /// Taken out when the process is left running after a timeout.
+    process: Option<Child>,
     stdin: ChildStdin,
     lines: Lines<BufReader<ChildStdout>>,
+    id: EngineId,
+    options: HashMap<String, UciOption>,
+    logger: Box<ProtocolLogger>,
+    position: Option<Position>,
+    chess960: bool,
+    timeouts: Option<Timeouts>,
+    deadline: Option<Deadline>,
+    #[cfg(feature = "board")]
+    validate_moves: bool,
+}
+
+/// The engine's identity, as sent with "id name" and "id author".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineId {
+    pub name: Option<String>,
+    pub author: Option<String>,
 }
 
 impl Engine {
-    pub fn from_path(path: String, handle: &Handle) -> impl Future<Item = Engine, Error = ()> {
-        let process = Command::new(path)
+    pub fn from_path(
+        path: String,
+        handle: &Handle,
+    ) -> impl Future<Item = Engine, Error = UciError> {
+        future::result(Engine::spawn(path, handle)).and_then(Engine::new)
+    }
+
+    /// Same as `from_path`, but the engine has to answer within `timeouts`,
+    /// starting with the handshake.
+    pub fn from_path_with_timeouts(
+        path: String,
+        handle: &Handle,
+        timeouts: Timeouts,
+    ) -> impl Future<Item = Engine, Error = UciError> {
+        future::result(Engine::spawn(path, handle))
+            .and_then(|process| Engine::with_timeouts(process, Box::new(SilentLogger), timeouts))
+    }
+
+    fn spawn(path: String, handle: &Handle) -> Result<Child, UciError> {
+        Command::new(path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .spawn_async(&handle)
-            .expect("failed to spawn chess engine");
+            .spawn_async(handle)
+            .map_err(UciError::Spawn)
+    }
 
-        Engine::new(process)
+    /// Performs the "uci" handshake with an already spawned engine.
+    ///
+    /// The engine's stdin and stdout must be piped.
+    pub fn new(process: tokio_process::Child) -> impl Future<Item = Engine, Error = UciError> {
+        Engine::with_logger(process, Box::new(SilentLogger))
     }
 
-    pub fn new(mut process: tokio_process::Child) -> impl Future<Item = Engine, Error = ()> {
-        let lines = lines(BufReader::new(process.stdout().take().unwrap()));
-        let stdin = process.stdin().take().unwrap();
+    /// Same as `new`, but every line exchanged with the engine, starting
+    /// with the handshake, is passed to `logger`.
+    pub fn with_logger(
+        process: tokio_process::Child,
+        logger: Box<ProtocolLogger>,
+    ) -> impl Future<Item = Engine, Error = UciError> {
+        Engine::handshake(process, logger, None, false)
+    }
 
-        let mut engine = Engine {
-            process,
-            stdin,
-            lines,
-        };
+    /// Same as `with_logger`, but the engine has to answer within
+    /// `timeouts`, starting with the handshake.
+    pub fn with_timeouts(
+        process: tokio_process::Child,
+        logger: Box<ProtocolLogger>,
+        timeouts: Timeouts,
+    ) -> impl Future<Item = Engine, Error = UciError> {
+        Engine::handshake(process, logger, Some(timeouts), false)
+    }
 
+    /// Same as `with_logger`, but Chess960 mode is turned on right after
+    /// the handshake, with `timeouts` if given. Fails with
+    /// `UciError::Option` if the engine does not advertise "UCI_Chess960".
+    pub fn with_chess960(
+        process: tokio_process::Child,
+        logger: Box<ProtocolLogger>,
+        timeouts: Option<Timeouts>,
+    ) -> impl Future<Item = Engine, Error = UciError> {
+        Engine::handshake(process, logger, timeouts, true)
+    }
+
+    fn handshake(
+        mut process: tokio_process::Child,
+        logger: Box<ProtocolLogger>,
+        timeouts: Option<Timeouts>,
+        chess960: bool,
+    ) -> impl Future<Item = Engine, Error = UciError> {
         async_block! {
-            engine = await!(engine.write("uci\n".to_string())).expect("failed to write to engine");
-            engine = await!(engine.wait_for(EngineMessage::UciOk)).expect("failed to wait for uciok");
+            let stdout = process.stdout().take().ok_or_else(|| {
+                UciError::Spawn(io::Error::new(io::ErrorKind::Other, "engine stdout is not piped"))
+            })?;
+            let stdin = process.stdin().take().ok_or_else(|| {
+                UciError::Spawn(io::Error::new(io::ErrorKind::Other, "engine stdin is not piped"))
+            })?;
+
+            let mut engine = Engine {
+                process: Some(process),
+                stdin,
+                lines: lines(BufReader::new(stdout)),
+                id: EngineId::default(),
+                options: HashMap::new(),
+                logger,
+                position: None,
+                chess960: false,
+                timeouts,
+                deadline: None,
+                #[cfg(feature = "board")]
+                validate_moves: false,
+            };
+
+            engine.start_timer(TimeoutKind::Handshake, Duration::from_secs(0))?;
+            engine = await!(engine.write("uci\n".to_string()))?;
+
+            loop {
+                let pair = await!(engine.parse_line())?;
+                let message = pair.0;
+                engine = pair.1;
+
+                match message {
+                    EngineMessage::Id(IdField::Name(name)) => engine.id.name = Some(name),
+                    EngineMessage::Id(IdField::Author(author)) => engine.id.author = Some(author),
+                    EngineMessage::UciOption(option) => {
+                        engine.options.insert(option.name.clone(), option);
+                    }
+                    EngineMessage::UciOk => break,
+                    EngineMessage::BestMove(_) => {
+                        return Err(UciError::Protocol("bestmove before uciok".to_string()))
+                    }
+                    _ => {}
+                }
+            }
 
+            engine.deadline = None;
+            if chess960 {
+                engine = await!(engine.set_chess960(true))?;
+            }
             Ok(engine)
         }
     }
 
+    /// Replaces the logger that receives the protocol traffic.
+    pub fn set_logger(&mut self, logger: Box<ProtocolLogger>) {
+        self.logger = logger;
+    }
+
+    /// Replaces the timeouts the engine has to answer within.
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = Some(timeouts);
+    }
+
+    /// Checks every "bestmove", and the ponder move after it, against the
+    /// last position sent with `set_position`. Illegal moves are reported as
+    /// `UciError::IllegalMove`.
+    #[cfg(feature = "board")]
+    pub fn set_move_validation(&mut self, enabled: bool) {
+        self.validate_moves = enabled;
+    }
+
+    /// The engine's name and author, as sent during the handshake.
+    pub fn id(&self) -> &EngineId {
+        &self.id
+    }
+
+    /// The options the engine advertised during the handshake, by name.
+    pub fn options(&self) -> &HashMap<String, UciOption> {
+        &self.options
+    }
+
+    /// Looks up an advertised option. Option names are not case sensitive.
+    pub fn option(&self, name: &str) -> Option<&UciOption> {
+        self.options
+            .values()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the engine advertised the "UCI_Chess960" option.
+    pub fn supports_chess960(&self) -> bool {
+        match self.option(CHESS960_OPTION) {
+            Some(option) => match option.kind {
+                UciOptionType::Check { .. } => true,
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    /// How the engine currently writes castling moves.
+    pub fn castling_mode(&self) -> CastlingMode {
+        if self.chess960 {
+            CastlingMode::Chess960
+        } else {
+            CastlingMode::Standard
+        }
+    }
+
+    /// Turns Chess960 mode on or off with the "UCI_Chess960" option.
+    ///
+    /// While it is on, castling moves are sent and read as the king taking
+    /// its own rook, and positions can use X-FEN or Shredder-FEN.
+    pub fn set_chess960(self, enabled: bool) -> impl Future<Item = Engine, Error = UciError> {
+        self.set_option(CHESS960_OPTION.to_string(), UciOptionValue::Check(enabled))
+    }
+
     /// Waits for the engine to be ready to accept more commands.
     ///
     /// Sends the "isready" command to the engine and waits for a
-    /// "readyok" response.
+    /// "readyok" response, for no longer than the sync timeout of the
+    /// engine's `Timeouts`.
     #[async]
-    pub fn sync(self) -> Result<Self, ()> {
+    pub fn sync(self) -> Result<Self, UciError> {
         let mut engine = self;
+        engine.start_timer(TimeoutKind::Sync, Duration::from_secs(0))?;
         engine = await!(engine.write("isready\n".to_string()))?;
-        println!("waiting for uciok");
         engine = await!(engine.wait_for(EngineMessage::ReadyOk))?;
-        println!("got uciok");
+        engine.deadline = None;
         Ok(engine)
     }
 
     #[async]
-    fn wait_for(self, message: EngineMessage) -> Result<Self, ()> {
+    fn wait_for(self, message: EngineMessage) -> Result<Self, UciError> {
         let mut engine = self;
 
         loop {
-            let pair = await!(engine.parse_line()).expect("Couldn't parse line");
+            let pair = await!(engine.parse_line())?;
             let m = pair.0;
             engine = pair.1;
 
@@ -90,56 +316,197 @@ impl Engine {
     /// This sends the "ucinewgame" command to the engine and then calls
     /// the sync() method.
     #[async]
-    pub fn new_game(self) -> Result<Self, ()> {
-        let engine =
-            await!(self.write("ucinewgame\n".to_string())).expect("failed to write to engine");
+    pub fn new_game(self) -> Result<Self, UciError> {
+        let engine = await!(self.write("ucinewgame\n".to_string()))?;
         await!(engine.sync())
     }
 
-    pub fn set_option(self) {
-        unimplemented!();
+    /// Sets an option with the "setoption" command.
+    ///
+    /// The value is checked against the option declarations the engine sent
+    /// during the handshake and nothing is written if it is rejected. Names
+    /// and values containing a line break are always rejected.
+    #[async]
+    pub fn set_option(self, name: String, value: UciOptionValue) -> Result<Self, UciError> {
+        check_line_break(&name)?;
+        let command = {
+            let option = self.option(&name)
+                .ok_or_else(|| OptionError::UnknownOption(name.clone()))?;
+            option.validate(&value)?;
+            format!("setoption name {} value {}\n", option.name, value)
+        };
+
+        let mut engine = await!(self.write(command))?;
+        if name.eq_ignore_ascii_case(CHESS960_OPTION) {
+            engine.chess960 = value == UciOptionValue::Check(true);
+        }
+        Ok(engine)
     }
 
+    /// Presses a button option, such as "Clear Hash".
     #[async]
-    pub fn set_position(self, params: String) -> Result<Self, ()> {
-        await!(self.write(format!("position {}\n", params)))
+    pub fn press_button(self, name: String) -> Result<Self, UciError> {
+        check_line_break(&name)?;
+        let command = {
+            let option = self.option(&name)
+                .ok_or_else(|| OptionError::UnknownOption(name.clone()))?;
+            if option.kind != UciOptionType::Button {
+                return Err(OptionError::TypeMismatch(option.name.clone()).into());
+            }
+            format!("setoption name {}\n", option.name)
+        };
+
+        await!(self.write(command))
     }
 
+    /// Sets up the position to search with the "position" command.
+    ///
+    /// A FEN that does not describe a valid position is rejected before
+    /// anything is sent, and so is a move that is not legal when the `board`
+    /// feature is enabled, as `UciError::Position`.
     #[async]
-    pub fn go(self, params: String) -> Result<(Self, BestMove), ()> {
-        let mut engine =
-            await!(self.write(format!("go {}\n", params))).expect("failed to write to engine");
+    pub fn set_position(self, position: Position) -> Result<Self, UciError> {
+        if let Position::Fen { ref fen, .. } = position {
+            fen.validate()?;
+        }
+        search::validate_position(&position, self.castling_mode())?;
 
-        loop {
-            let pair = await!(engine.parse_line()).expect("Couldn't parse line");
-            let message = pair.0;
-            engine = pair.1;
+        let mut engine = await!(self.write(format!("position {}\n", position)))?;
+        engine.position = Some(position);
+        Ok(engine)
+    }
 
-            match message {
-                EngineMessage::BestMove(best_move) => return Ok((engine, best_move)),
-                _ => println!("{:?}", message),
-            }
+    /// Starts a search with the "go" command.
+    ///
+    /// The returned handle owns the engine until the search is over and
+    /// can be used to stop it. Contradictory parameters are rejected before
+    /// anything is sent.
+    ///
+    /// With a search grace period set in the engine's `Timeouts`, the search
+    /// times out when no "bestmove" arrives within its "movetime", or the
+    /// side to move's remaining time, plus the grace period.
+    #[async]
+    pub fn go(self, params: GoParams) -> Result<SearchHandle, UciError> {
+        params.validate()?;
+        let validator = search::validator(&self)?;
+
+        let mut engine = self;
+        let side = engine
+            .position
+            .as_ref()
+            .map_or(Color::White, Position::side_to_move);
+        if let Some(limit) = params.time_limit(side) {
+            engine.start_timer(TimeoutKind::Search, limit)?;
         }
+
+        let engine = await!(engine.write(format!("go {}\n", params)))?;
+        Ok(SearchHandle::new(engine, validator))
     }
 
-    pub fn stop(self) {
-        unimplemented!();
+    /// Starts pondering once the engine played `best_move` in `position`.
+    ///
+    /// The ponder move of `best_move` is added to the position and searched
+    /// with "go ponder" and `params`, which should hold the clocks as they
+    /// are after the engine's move. The returned handle is resolved with
+    /// the opponent's actual move.
+    #[async]
+    pub fn go_ponder(
+        self,
+        position: Position,
+        best_move: BestMove,
+        params: GoParams,
+    ) -> Result<PonderHandle, UciError> {
+        let (played, ponder) = match (best_move.best_move, best_move.ponder) {
+            (Some(played), Some(ponder)) => (played, ponder),
+            _ => return Err(UciError::NoPonderMove),
+        };
+
+        let mut position = position;
+        position.push(played);
+        let mut pondered = position.clone();
+        pondered.push(ponder);
+
+        let engine = await!(self.set_position(pondered))?;
+        let search = await!(engine.go(params.ponder()))?;
+        Ok(PonderHandle::new(search, position, ponder))
     }
 
-    pub fn ponder_hit(self) -> impl Future<Item = Engine, Error = ()> {
-        self.write("ponderhit".to_string())
+    /// Sends "ponderhit". A search started with `go_ponder` should be
+    /// continued with `PonderHandle::opponent_moved` instead.
+    pub fn ponder_hit(self) -> impl Future<Item = Engine, Error = UciError> {
+        self.write("ponderhit\n".to_string())
     }
 
     #[async]
-    pub fn quit(self) -> Result<(), std::io::Error> {
+    pub fn quit(self) -> Result<(), UciError> {
         let mut self2 = self;
-        self2 = await!(self2.write("quit\n".to_string())).unwrap();
-        await!(self2.process.wait_with_output()).unwrap();
+        self2 = await!(self2.write("quit\n".to_string()))?;
+        if let Some(process) = self2.process.take() {
+            await!(process.wait_with_output())?;
+        }
+        Ok(())
+    }
+
+    pub fn kill(&mut self) -> Result<(), UciError> {
+        match self.process {
+            Some(ref mut process) => process.kill().map_err(UciError::Io),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts the timer for `kind`, if `Timeouts` sets one, replacing the
+    /// running one. `extra` is added to the configured duration.
+    pub(crate) fn start_timer(&mut self, kind: TimeoutKind, extra: Duration) -> io::Result<()> {
+        self.deadline = match self.timeouts {
+            Some(ref timeouts) => timeouts.deadline(kind, extra)?,
+            None => None,
+        };
         Ok(())
     }
 
-    pub fn kill(&mut self) {
-        self.process.kill().expect("failed to kill engine");
+    pub(crate) fn stop_timer(&mut self) {
+        self.deadline = None;
+    }
+
+    /// Kills the engine, or lets its process go, once it failed to answer
+    /// in time.
+    fn time_out(&mut self, kind: TimeoutKind) -> UciError {
+        self.deadline = None;
+
+        let kill = self.timeouts.as_ref().map_or(true, Timeouts::kills);
+        if kill {
+            // The engine is unusable either way, so a failure to kill it
+            // is not worth reporting over the timeout.
+            let _ = self.kill();
+        } else if let Some(process) = self.process.take() {
+            process.forget();
+        }
+
+        UciError::Timeout(kind)
+    }
+
+    /// Reads the next line from the engine, failing once the running timer
+    /// expires, even if the engine keeps sending lines.
+    pub(crate) fn poll_line(&mut self) -> Poll<String, UciError> {
+        let expired = match self.deadline {
+            Some(ref mut deadline) => match deadline.poll()? {
+                Async::Ready(()) => Some(deadline.kind),
+                Async::NotReady => None,
+            },
+            None => None,
+        };
+        if let Some(kind) = expired {
+            return Err(self.time_out(kind));
+        }
+
+        let line = match self.lines.poll()? {
+            Async::Ready(Some(line)) => line,
+            Async::Ready(None) => return Err(UciError::UnexpectedEof),
+            Async::NotReady => return Ok(Async::NotReady),
+        };
+
+        self.log(Direction::FromEngine, &line);
+        Ok(Async::Ready(line))
     }
 
     /// Writes in the engine's stdin buffer
@@ -163,60 +530,48 @@ impl Engine {
     /// }
     /// ```
     #[async]
-    pub fn write(self, message: String) -> Result<Self, ()> {
-        print!("[gui -> engine] {}", message);
+    pub fn write(self, message: String) -> Result<Self, UciError> {
+        let mut engine = self;
+        engine.log(Direction::ToEngine, message.trim_right());
 
-        let lines = self.lines;
-        let stdin = self.stdin;
-        let process = self.process;
+        let stdin = engine.stdin;
+        let (stdin, _) = await!(write_all(stdin, message.into_bytes()))?;
 
-        let res = await!(write_all(stdin, message.into_bytes()));
+        Ok(Engine { stdin, ..engine })
+    }
 
-        match res {
-            Ok((stdin, _)) => Ok(Engine {
-                process,
-                stdin,
-                lines,
-            }),
-            Err(_) => panic!("failed to write to engine"),
-        }
+    pub fn read_line(self) -> impl Future<Item = (String, Self), Error = UciError> {
+        let mut engine = Some(self);
+
+        future::poll_fn(move || {
+            let line = match engine
+                .as_mut()
+                .expect("read_line polled after completion")
+                .poll_line()?
+            {
+                Async::Ready(line) => line,
+                Async::NotReady => return Ok(Async::NotReady),
+            };
+
+            Ok(Async::Ready((line, engine.take().unwrap())))
+        })
     }
 
     #[async]
-    pub fn read_line(self) -> Result<(String, Self), ()> {
-        let lines = self.lines;
-        let stdin = self.stdin;
-        let process = self.process;
-
-        let res = await!(lines.into_future());
-
-        match res {
-            Ok((l, stream)) => {
-                let l = l.unwrap();
-                let new_self = Engine {
-                    process,
-                    stdin,
-                    lines: stream,
-                };
-                println!("[engine -> gui] {}", l);
-
-                Ok((l, new_self))
-            }
-            Err(_) => panic!("error reading line"),
-        }
+    pub fn parse_line(self) -> Result<(EngineMessage, Self), UciError> {
+        let (line, self2) = await!(self.read_line())?;
+        Ok((parse_message(line)?, self2))
     }
 
-    #[async]
-    pub fn parse_line(self) -> Result<(EngineMessage, Self), ()> {
-        let (line, self2) = await!(self.read_line()).unwrap();
+    fn log(&mut self, direction: Direction, line: &str) {
+        self.logger.log(direction, SystemTime::now(), line);
+    }
+}
 
-        match engine_message(CompleteStr(&line)) {
-            Ok((_, message)) => Ok((message, self2)),
-            e => {
-                println!("{:?}", e);
-                panic!("error parsing line")
-            } // TODO: better error management
-        }
+fn parse_message(line: String) -> Result<EngineMessage, UciError> {
+    match engine_message(CompleteStr(&line)) {
+        Ok((_, message)) => Ok(message),
+        Err(_) => Err(UciError::Parse(line)),
     }
 }
 
@@ -229,12 +584,145 @@ mod tests {
     fn engine_test() {
         let mut core = Core::new().unwrap();
         let engine_future = Engine::from_path("stockfish".to_string(), &core.handle());
-        let mut engine = core.run(engine_future).unwrap();
-        engine = core.run(engine.go("nodes 1000".to_string())).unwrap().0;
+        let engine = core.run(engine_future).unwrap();
+        let search = core.run(engine.go(GoParams::new().nodes(1000))).unwrap();
+        let (mut engine, best_move) = core.run(search.wait()).unwrap();
         engine = core.run(engine.ponder_hit()).unwrap();
-        engine = core.run(engine.set_position("e2e4 e7e5".to_string()))
-            .unwrap();
-        engine = core.run(engine.go("nodes 1000".to_string())).unwrap().0;
+        let mut position = Position::startpos();
+        position.push(best_move.best_move.unwrap());
+        engine = core.run(engine.set_position(position)).unwrap();
+        let search = core.run(engine.go(GoParams::new().infinite())).unwrap();
+        let (info, search) = core.run(search.into_future()).map_err(|(e, _)| e).unwrap();
+        assert!(info.is_some());
+        engine = core.run(search.stop()).unwrap().0;
         core.run(engine.quit()).unwrap();
     }
+
+    /// Runs `script` with `sh` as a stand-in engine.
+    pub(crate) fn fake_engine(handle: &Handle, script: &str) -> tokio_process::Child {
+        Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn_async(handle)
+            .unwrap()
+    }
+
+    #[test]
+    fn chess960_handshake_test() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        let script = "read l; echo 'option name UCI_Chess960 type check default false'; \
+                      echo uciok; cat > /dev/null";
+        let engine_future =
+            Engine::with_chess960(fake_engine(&handle, script), Box::new(SilentLogger), None);
+        let engine = core.run(engine_future).unwrap();
+        assert_eq!(engine.castling_mode(), CastlingMode::Chess960);
+
+        let script = "read l; echo uciok; cat > /dev/null";
+        let engine_future =
+            Engine::with_chess960(fake_engine(&handle, script), Box::new(SilentLogger), None);
+        match core.run(engine_future) {
+            Err(UciError::Option(OptionError::UnknownOption(_))) => {}
+            _ => panic!("Chess960 enabled without the option"),
+        }
+    }
+
+    #[test]
+    fn chatty_search_timeout_test() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        let script = "read l; echo uciok; while true; do echo 'info depth 1'; sleep 0.01; done";
+        let timeouts = Timeouts::new(&handle).search_grace(Duration::from_millis(100));
+        let engine_future =
+            Engine::with_timeouts(fake_engine(&handle, script), Box::new(SilentLogger), timeouts);
+        let engine = core.run(engine_future).unwrap();
+
+        let params = GoParams::new().movetime(Duration::from_millis(10));
+        let search = core.run(engine.go(params)).unwrap();
+        match core.run(search.wait()) {
+            Err(UciError::Timeout(TimeoutKind::Search)) => {}
+            _ => panic!("the search did not time out"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "board")]
+    fn move_validation_test() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        let script = "read l; echo uciok; \
+                      while read l; do case $l in go*) echo 'bestmove e2e5';; esac; done";
+
+        let engine = core.run(Engine::new(fake_engine(&handle, script))).unwrap();
+        let mut position = Position::startpos();
+        position.push("e2e5".parse().unwrap());
+        match core.run(engine.set_position(position)) {
+            Err(UciError::Position { .. }) => {}
+            _ => panic!("position with an illegal move accepted"),
+        }
+
+        let mut engine = core.run(Engine::new(fake_engine(&handle, script))).unwrap();
+        engine.set_move_validation(true);
+        let engine = core.run(engine.set_position(Position::startpos())).unwrap();
+        let search = core.run(engine.go(GoParams::new().depth(1))).unwrap();
+        match core.run(search.wait()) {
+            Err(UciError::IllegalMove { .. }) => {}
+            _ => panic!("illegal best move accepted"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "board")]
+    fn ponder_test() {
+        let mut core = Core::new().unwrap();
+        let handle = core.handle();
+        // Pondering searches only end with "ponderhit" or "stop", and the
+        // engine only finds d2d4 once it is told about the move played.
+        let script = "while read command rest; do case $command in \
+                      uci) echo uciok;; isready) echo readyok;; \
+                      position) position=$rest;; \
+                      go) case \"$rest $position\" in \
+                          ponder*) ;; \
+                          *g8f6) echo 'bestmove d2d4';; \
+                          *) echo 'bestmove e2e4 ponder e7e5';; \
+                          esac;; \
+                      ponderhit) echo 'bestmove g1f3 ponder b8c6';; \
+                      stop) echo 'bestmove f1c4';; \
+                      quit) exit;; esac; done";
+        let mut engine = core.run(Engine::new(fake_engine(&handle, script))).unwrap();
+        engine.set_move_validation(true);
+        let params = || GoParams::new().nodes(1);
+        let m = |s: &str| -> Move { s.parse().unwrap() };
+
+        let mut position = Position::startpos();
+        let engine = core.run(engine.set_position(position.clone())).unwrap();
+        let search = core.run(engine.go(params())).unwrap();
+        let (engine, best_move) = core.run(search.wait()).unwrap();
+
+        let pondering = core.run(engine.go_ponder(position.clone(), best_move, params()))
+            .unwrap();
+        assert_eq!(pondering.ponder_move(), m("e7e5"));
+        let search = core.run(pondering.opponent_moved(m("e7e5"), params())).unwrap();
+        let (engine, hit) = core.run(search.wait()).unwrap();
+        assert_eq!(hit.best_move, Some(m("g1f3")));
+
+        position.push(m("e2e4"));
+        position.push(m("e7e5"));
+        let pondering = core.run(engine.go_ponder(position, hit, params())).unwrap();
+        assert_eq!(pondering.ponder_move(), m("b8c6"));
+        let search = core.run(pondering.opponent_moved(m("g8f6"), params())).unwrap();
+        let (engine, restarted) = core.run(search.wait()).unwrap();
+        assert_eq!(restarted.best_move, Some(m("d2d4")));
+
+        let no_ponder = BestMove {
+            best_move: best_move.best_move,
+            ponder: None,
+        };
+        match core.run(engine.go_ponder(Position::startpos(), no_ponder, params())) {
+            Err(UciError::NoPonderMove) => {}
+            _ => panic!("pondering without a ponder move"),
+        }
+    }
 }
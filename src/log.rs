@@ -0,0 +1,128 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Which way a line of the protocol travelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    ToEngine,
+    FromEngine,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Direction::ToEngine => write!(f, "gui -> engine"),
+            Direction::FromEngine => write!(f, "engine -> gui"),
+        }
+    }
+}
+
+/// Receives every line exchanged with the engine.
+///
+/// Closures taking the same arguments as `log` can be used directly.
+pub trait ProtocolLogger {
+    fn log(&mut self, direction: Direction, time: SystemTime, line: &str);
+}
+
+impl<F> ProtocolLogger for F
+where
+    F: FnMut(Direction, SystemTime, &str),
+{
+    fn log(&mut self, direction: Direction, time: SystemTime, line: &str) {
+        self(direction, time, line)
+    }
+}
+
+/// Discards all traffic. This is the default.
+#[derive(Debug, Default)]
+pub struct SilentLogger;
+
+impl ProtocolLogger for SilentLogger {
+    fn log(&mut self, _direction: Direction, _time: SystemTime, _line: &str) {}
+}
+
+/// Writes all traffic to stderr.
+#[derive(Debug, Default)]
+pub struct StderrLogger;
+
+impl ProtocolLogger for StderrLogger {
+    fn log(&mut self, direction: Direction, _time: SystemTime, line: &str) {
+        eprintln!("[{}] {}", direction, line);
+    }
+}
+
+/// Writes a timestamped transcript of all traffic, one line per message.
+///
+/// Each line looks like `[1523456789.123] [gui -> engine] isready`, where
+/// the timestamp is the number of seconds since the Unix epoch.
+pub struct TranscriptLogger<W: Write> {
+    writer: W,
+}
+
+impl TranscriptLogger<File> {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<TranscriptLogger<File>> {
+        Ok(TranscriptLogger::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> TranscriptLogger<W> {
+    pub fn new(writer: W) -> TranscriptLogger<W> {
+        TranscriptLogger { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ProtocolLogger for TranscriptLogger<W> {
+    fn log(&mut self, direction: Direction, time: SystemTime, line: &str) {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        // A transcript is a debugging aid, failing to write it should not
+        // interrupt the session.
+        let _ = writeln!(
+            self.writer,
+            "[{}.{:03}] [{}] {}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_nanos() / 1_000_000,
+            direction,
+            line
+        ).and_then(|_| self.writer.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn transcript_test() {
+        let mut logger = TranscriptLogger::new(Vec::new());
+        let time = UNIX_EPOCH + Duration::from_millis(1523456789123);
+        logger.log(Direction::ToEngine, time, "isready");
+        logger.log(Direction::FromEngine, time + Duration::from_millis(5), "readyok");
+
+        assert_eq!(
+            String::from_utf8(logger.into_inner()).unwrap(),
+            "[1523456789.123] [gui -> engine] isready\n[1523456789.128] [engine -> gui] readyok\n"
+        );
+    }
+
+    #[test]
+    fn closure_test() {
+        let mut lines = Vec::new();
+        {
+            let mut logger = |direction: Direction, _time: SystemTime, line: &str| {
+                lines.push(format!("{} {}", direction, line))
+            };
+            logger.log(Direction::FromEngine, SystemTime::now(), "uciok");
+        }
+
+        assert_eq!(lines, vec!["engine -> gui uciok".to_string()]);
+    }
+}
@@ -1,29 +1,42 @@
 use super::EngineMessage;
 use super::uci_move::{uci_move, Move};
-use nom::{space, types::CompleteStr};
+use nom::{space, types::CompleteStr, ErrorKind};
 
-#[derive(Debug, PartialEq, Eq)]
+/// The result of a search.
+///
+/// `best_move` is `None` when the engine has no move to play, which it
+/// reports as "bestmove (none)" or with the null move "bestmove 0000".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BestMove {
-    pub best_move: Move,
+    pub best_move: Option<Move>,
     pub ponder: Option<Move>,
 }
 
-named!(ponder<CompleteStr, Move>, do_parse!(
+named!(null_move<CompleteStr, CompleteStr>, alt!(tag!("(none)") | tag!("0000")));
+
+named!(maybe_move<CompleteStr, Option<Move>>, alt!(
+    map!(uci_move, Some) |
+    value!(None, null_move)
+));
+
+// A malformed move fails the whole line instead of reading it as an unknown
+// command, which would leave the search waiting for its best move.
+named!(ponder<CompleteStr, Option<Move>>, do_parse!(
     space >>
     tag!("ponder") >>
     space >>
-    ponder_move: uci_move >>
+    ponder_move: return_error!(ErrorKind::Custom(0), maybe_move) >>
     (ponder_move)
 ));
 
 named!(pub best_move<CompleteStr, EngineMessage>, do_parse!(
     tag!("bestmove") >>
     space >>
-    best_move: uci_move >>
+    best_move: return_error!(ErrorKind::Custom(0), maybe_move) >>
     ponder: opt!(ponder) >>
     (EngineMessage::BestMove(BestMove {
         best_move: best_move,
-        ponder: ponder
+        ponder: ponder.and_then(|p| p)
     }))
 ));
 
@@ -67,7 +80,7 @@ mod tests {
             Ok((
                 CompleteStr(""),
                 EngineMessage::BestMove(BestMove {
-                    best_move: g7g1,
+                    best_move: Some(g7g1),
                     ponder: Some(a1a7),
                 })
             ))
@@ -95,10 +108,32 @@ mod tests {
             Ok((
                 CompleteStr(""),
                 EngineMessage::BestMove(BestMove {
-                    best_move: f1h3,
+                    best_move: Some(f1h3),
                     ponder: None,
                 })
             ))
         );
     }
+
+    #[test]
+    fn none_test() {
+        let expected = Ok((
+            CompleteStr(""),
+            EngineMessage::BestMove(BestMove {
+                best_move: None,
+                ponder: None,
+            }),
+        ));
+
+        assert_eq!(best_move(CompleteStr("bestmove (none)")), expected);
+        assert_eq!(best_move(CompleteStr("bestmove 0000")), expected);
+        assert_eq!(best_move(CompleteStr("bestmove (none) ponder (none)")), expected);
+    }
+
+    #[test]
+    fn malformed_move_test() {
+        assert!(best_move(CompleteStr("bestmove e7e8k")).is_err());
+        assert!(best_move(CompleteStr("bestmove e2e4 ponder e7e5x")).is_err());
+        assert!(super::super::engine_message(CompleteStr("bestmove e7e8k")).is_err());
+    }
 }
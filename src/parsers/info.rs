@@ -0,0 +1,274 @@
+use super::EngineMessage;
+use super::score::{score, Score};
+use super::uci_move::{uci_move, Move};
+use super::{number, small_number, take_all};
+use nom::{space, types::CompleteStr};
+use std::time::Duration;
+
+/// Everything the engine reported in a single `info` line.
+///
+/// Engines only send the fields that changed, so every field is optional.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub time: Option<Duration>,
+    pub nodes: Option<u64>,
+    pub pv: Vec<Move>,
+    pub multipv: Option<u32>,
+    pub score: Option<Score>,
+    pub currmove: Option<Move>,
+    pub currmovenumber: Option<u32>,
+    /// Hash table usage, in permill.
+    pub hashfull: Option<u32>,
+    pub nps: Option<u64>,
+    pub tbhits: Option<u64>,
+    pub sbhits: Option<u64>,
+    /// CPU usage, in permill.
+    pub cpuload: Option<u32>,
+    pub string: Option<String>,
+    pub refutation: Vec<Move>,
+    pub currline: Option<CurrLine>,
+}
+
+/// The line currently being searched by one of the engine's CPUs.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CurrLine {
+    pub cpu: Option<u32>,
+    pub moves: Vec<Move>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum InfoField {
+    Depth(u32),
+    SelDepth(u32),
+    Time(Duration),
+    Nodes(u64),
+    Pv(Vec<Move>),
+    MultiPv(u32),
+    Score(Score),
+    CurrMove(Move),
+    CurrMoveNumber(u32),
+    HashFull(u32),
+    Nps(u64),
+    TbHits(u64),
+    SbHits(u64),
+    CpuLoad(u32),
+    String(String),
+    Refutation(Vec<Move>),
+    CurrLine(CurrLine),
+    Unknown,
+}
+
+impl SearchInfo {
+    fn from_fields(fields: Vec<InfoField>) -> SearchInfo {
+        let mut info = SearchInfo::default();
+
+        for field in fields {
+            match field {
+                InfoField::Depth(x) => info.depth = Some(x),
+                InfoField::SelDepth(x) => info.seldepth = Some(x),
+                InfoField::Time(x) => info.time = Some(x),
+                InfoField::Nodes(x) => info.nodes = Some(x),
+                InfoField::Pv(x) => info.pv = x,
+                InfoField::MultiPv(x) => info.multipv = Some(x),
+                InfoField::Score(x) => info.score = Some(x),
+                InfoField::CurrMove(x) => info.currmove = Some(x),
+                InfoField::CurrMoveNumber(x) => info.currmovenumber = Some(x),
+                InfoField::HashFull(x) => info.hashfull = Some(x),
+                InfoField::Nps(x) => info.nps = Some(x),
+                InfoField::TbHits(x) => info.tbhits = Some(x),
+                InfoField::SbHits(x) => info.sbhits = Some(x),
+                InfoField::CpuLoad(x) => info.cpuload = Some(x),
+                InfoField::String(x) => info.string = Some(x),
+                InfoField::Refutation(x) => info.refutation = x,
+                InfoField::CurrLine(x) => info.currline = Some(x),
+                InfoField::Unknown => {}
+            }
+        }
+
+        info
+    }
+}
+
+named!(moves<CompleteStr, Vec<Move>>, many1!(preceded!(space, uci_move)));
+
+named!(currline<CompleteStr, CurrLine>, do_parse!(
+    cpu: opt!(preceded!(space, small_number)) >>
+    moves: moves >>
+    (CurrLine {
+        cpu: cpu,
+        moves: moves
+    })
+));
+
+named!(info_field<CompleteStr, InfoField>, alt!(
+    do_parse!(tag!("depth") >> space >> x: small_number >> (InfoField::Depth(x))) |
+    do_parse!(tag!("seldepth") >> space >> x: small_number >> (InfoField::SelDepth(x))) |
+    do_parse!(
+        tag!("time") >> space >> x: number >> (InfoField::Time(Duration::from_millis(x)))
+    ) |
+    do_parse!(tag!("nodes") >> space >> x: number >> (InfoField::Nodes(x))) |
+    do_parse!(tag!("pv") >> x: moves >> (InfoField::Pv(x))) |
+    do_parse!(tag!("multipv") >> space >> x: small_number >> (InfoField::MultiPv(x))) |
+    do_parse!(tag!("score") >> space >> x: score >> (InfoField::Score(x))) |
+    do_parse!(tag!("currmove") >> space >> x: uci_move >> (InfoField::CurrMove(x))) |
+    do_parse!(
+        tag!("currmovenumber") >> space >> x: small_number >> (InfoField::CurrMoveNumber(x))
+    ) |
+    do_parse!(tag!("hashfull") >> space >> x: small_number >> (InfoField::HashFull(x))) |
+    do_parse!(tag!("nps") >> space >> x: number >> (InfoField::Nps(x))) |
+    do_parse!(tag!("tbhits") >> space >> x: number >> (InfoField::TbHits(x))) |
+    do_parse!(tag!("sbhits") >> space >> x: number >> (InfoField::SbHits(x))) |
+    do_parse!(tag!("cpuload") >> space >> x: small_number >> (InfoField::CpuLoad(x))) |
+    do_parse!(tag!("string") >> space >> x: take_all >> (InfoField::String(x.0.to_string()))) |
+    do_parse!(tag!("refutation") >> x: moves >> (InfoField::Refutation(x))) |
+    do_parse!(tag!("currline") >> x: currline >> (InfoField::CurrLine(x))) |
+    do_parse!(is_not!(" \t") >> (InfoField::Unknown))
+));
+
+named!(pub info<CompleteStr, EngineMessage>, do_parse!(
+    tag!("info") >>
+    fields: many0!(preceded!(space, info_field)) >>
+    (EngineMessage::Info(SearchInfo::from_fields(fields)))
+));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::score::Bound;
+    use super::super::uci_move::{File, Rank, Square};
+
+    const EMPTY_SLICE: CompleteStr = CompleteStr("");
+
+    fn e2e4() -> Move {
+        Move {
+            from: Square {
+                file: File::E,
+                rank: Rank::Second,
+            },
+            to: Square {
+                file: File::E,
+                rank: Rank::Fourth,
+            },
+            promotion_piece: None,
+        }
+    }
+
+    fn b7b6() -> Move {
+        Move {
+            from: Square {
+                file: File::B,
+                rank: Rank::Seventh,
+            },
+            to: Square {
+                file: File::B,
+                rank: Rank::Sixth,
+            },
+            promotion_piece: None,
+        }
+    }
+
+    #[test]
+    fn pv_test() {
+        assert_eq!(
+            info(CompleteStr(
+                "info depth 2 seldepth 2 multipv 1 score cp 93 nodes 47 nps 23500 tbhits 0 time 2 pv e2e4 b7b6"
+            )),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(2),
+                    seldepth: Some(2),
+                    multipv: Some(1),
+                    score: Some(Score::Centipawns(93, Bound::Exact)),
+                    nodes: Some(47),
+                    nps: Some(23500),
+                    tbhits: Some(0),
+                    time: Some(Duration::from_millis(2)),
+                    pv: vec![e2e4(), b7b6()],
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn currmove_test() {
+        assert_eq!(
+            info(CompleteStr("info depth 29 currmove e2e4 currmovenumber 8")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(29),
+                    currmove: Some(e2e4()),
+                    currmovenumber: Some(8),
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn string_test() {
+        assert_eq!(
+            info(CompleteStr("info string NNUE evaluation using nn-62ef826d1a6d.nnue enabled")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    string: Some("NNUE evaluation using nn-62ef826d1a6d.nnue enabled".to_string()),
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn refutation_and_currline_test() {
+        assert_eq!(
+            info(CompleteStr("info refutation e2e4 b7b6 currline 1 b7b6 e2e4")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    refutation: vec![e2e4(), b7b6()],
+                    currline: Some(CurrLine {
+                        cpu: Some(1),
+                        moves: vec![b7b6(), e2e4()],
+                    }),
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn load_test() {
+        assert_eq!(
+            info(CompleteStr("info hashfull 120 cpuload 998 sbhits 3")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    hashfull: Some(120),
+                    cpuload: Some(998),
+                    sbhits: Some(3),
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn unknown_field_test() {
+        assert_eq!(
+            info(CompleteStr("info depth 10 wdl 10 20 970 nodes 1000")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(10),
+                    nodes: Some(1000),
+                    ..Default::default()
+                })
+            ))
+        );
+    }
+}
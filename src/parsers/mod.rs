@@ -1,32 +1,68 @@
 #![allow(dead_code)]
 
 mod best_move;
+mod info;
+mod option;
+mod score;
 mod uci_move;
 
 use self::best_move::best_move;
-use nom::{space, types::CompleteStr};
+use self::info::info;
+use self::option::option;
+use nom::{digit, space, types::CompleteStr};
 
 pub use self::best_move::BestMove;
-pub use self::uci_move::{File, Move, PromotionPiece, Rank};
+pub use self::info::{CurrLine, SearchInfo};
+pub use self::option::{check_line_break, OptionError, UciOption, UciOptionType, UciOptionValue};
+pub use self::score::{Bound, Score};
+pub use self::uci_move::{File, Move, ParseMoveError, PromotionPiece, Rank, Square};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum EngineMessage {
-    Id,
+    Id(IdField),
     UciOk,
     ReadyOk,
     BestMove(BestMove),
-    Info,
-    UciOption,
+    Info(SearchInfo),
+    UciOption(UciOption),
     UnknownCommand,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum IdField {
+    Name(String),
+    Author(String),
+}
+
 named!(take_all<CompleteStr, CompleteStr>, take_while!(|_| true));
 
+named!(number<CompleteStr, u64>, map_res!(
+    digit,
+    | input: CompleteStr | input.0.parse::<u64>()
+));
+
+named!(small_number<CompleteStr, u32>, map_res!(
+    digit,
+    | input: CompleteStr | input.0.parse::<u32>()
+));
+
+named!(signed_number<CompleteStr, i32>, map_res!(
+    recognize!(pair!(opt!(tag!("-")), digit)),
+    | input: CompleteStr | input.0.parse::<i32>()
+));
+
+named!(id_field<CompleteStr, IdField>, alt!(
+    do_parse!(tag!("name") >> space >> name: take_all >> (IdField::Name(name.0.to_string()))) |
+    do_parse!(
+        tag!("author") >> space >> author: take_all >> (IdField::Author(author.0.to_string()))
+    )
+));
+
 named!(id<CompleteStr, EngineMessage>, do_parse!(
     tag!("id") >>
     space >>
-    take_all >>
-    (EngineMessage::Id)
+    field: id_field >>
+    (EngineMessage::Id(field))
 ));
 
 named!(
@@ -39,20 +75,6 @@ named!(ready_ok<CompleteStr, EngineMessage>, do_parse!(
     (EngineMessage::ReadyOk)
 ));
 
-named!(info<CompleteStr, EngineMessage>, do_parse!(
-    tag!("info") >>
-    space >>
-    take_all >>
-    (EngineMessage::Info)
-));
-
-named!(option<CompleteStr, EngineMessage>, do_parse!(
-    tag!("option") >>
-    space >>
-    take_all >>
-    (EngineMessage::UciOption)
-));
-
 named!(unknown_command<CompleteStr, EngineMessage>, do_parse!(
     take_all >>
     (EngineMessage::UnknownCommand)
@@ -75,18 +97,27 @@ named!(pub engine_message<CompleteStr, EngineMessage>, do_parse!(
 mod tests {
     use super::*;
     use super::uci_move::{File, Move, Rank, Square};
+    use std::time::Duration;
 
     #[test]
     fn id_test() {
         assert_eq!(
             engine_message(CompleteStr("id name Stockfish 8 64")),
-            Ok((CompleteStr(""), EngineMessage::Id))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Id(IdField::Name("Stockfish 8 64".to_string()))
+            ))
         );
         assert_eq!(
             engine_message(CompleteStr(
                 "id author T. Romstad, M. Costalba, J. Kiiski, G. Linscott"
             )),
-            Ok((CompleteStr(""), EngineMessage::Id))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Id(IdField::Author(
+                    "T. Romstad, M. Costalba, J. Kiiski, G. Linscott".to_string()
+                ))
+            ))
         );
     }
 
@@ -127,7 +158,7 @@ mod tests {
             Ok((
                 CompleteStr(""),
                 EngineMessage::BestMove(BestMove {
-                    best_move: e2e3,
+                    best_move: Some(e2e3),
                     ponder: None,
                 })
             ))
@@ -136,25 +167,82 @@ mod tests {
 
     #[test]
     fn info_test() {
+        let e2 = Square {
+            file: File::E,
+            rank: Rank::Second,
+        };
+        let e4 = Square {
+            file: File::E,
+            rank: Rank::Fourth,
+        };
+        let e2e4 = Move {
+            from: e2,
+            to: e4,
+            promotion_piece: None,
+        };
+
         assert_eq!(
-            engine_message(CompleteStr("info depth 1 seldepth 1 multipv 1 score cp 90 nodes 20 nps 20000 tbhits 0 time 1 pv e2e4")), Ok((CompleteStr(""), EngineMessage::Info))
-        );
-        assert_eq!(
-            engine_message(CompleteStr("info depth 2 seldepth 2 multipv 1 score cp 93 nodes 47 nps 23500 tbhits 0 time 2 pv e2e4 b7b6")),
-            Ok((CompleteStr(""), EngineMessage::Info))
+            engine_message(CompleteStr("info depth 1 seldepth 1 multipv 1 score cp 90 nodes 20 nps 20000 tbhits 0 time 1 pv e2e4")),
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(1),
+                    seldepth: Some(1),
+                    multipv: Some(1),
+                    score: Some(Score::Centipawns(90, Bound::Exact)),
+                    nodes: Some(20),
+                    nps: Some(20000),
+                    tbhits: Some(0),
+                    time: Some(Duration::from_millis(1)),
+                    pv: vec![e2e4],
+                    ..Default::default()
+                })
+            ))
         );
+
+        let m = |s: &str| -> Move { s.parse().unwrap() };
         assert_eq!(
             engine_message(CompleteStr("info depth 3 seldepth 3 multipv 1 score cp 119 nodes 133 nps 66500 tbhits 0 time 2 pv d2d4 d7d6 e2e4")),
-            Ok((CompleteStr(""), EngineMessage::Info))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(3),
+                    seldepth: Some(3),
+                    multipv: Some(1),
+                    score: Some(Score::Centipawns(119, Bound::Exact)),
+                    nodes: Some(133),
+                    nps: Some(66500),
+                    tbhits: Some(0),
+                    time: Some(Duration::from_millis(2)),
+                    pv: vec![m("d2d4"), m("d7d6"), e2e4],
+                    ..Default::default()
+                })
+            ))
         );
 
         assert_eq!(
             engine_message(CompleteStr("info depth 29 currmove d2d4 currmovenumber 1")),
-            Ok((CompleteStr(""), EngineMessage::Info))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(29),
+                    currmove: Some(m("d2d4")),
+                    currmovenumber: Some(1),
+                    ..Default::default()
+                })
+            ))
         );
         assert_eq!(
             engine_message(CompleteStr("info depth 29 currmove c2c4 currmovenumber 8")),
-            Ok((CompleteStr(""), EngineMessage::Info))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::Info(SearchInfo {
+                    depth: Some(29),
+                    currmove: Some(m("c2c4")),
+                    currmovenumber: Some(8),
+                    ..Default::default()
+                })
+            ))
         );
     }
 
@@ -164,25 +252,47 @@ mod tests {
             engine_message(CompleteStr(
                 "option name Debug Log File type string default"
             )),
-            Ok((CompleteStr(""), EngineMessage::UciOption))
-        );
-        assert_eq!(
-            engine_message(CompleteStr(
-                "option name Contempt type spin default 0 min -100 max 100"
-            )),
-            Ok((CompleteStr(""), EngineMessage::UciOption))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::UciOption(UciOption {
+                    name: "Debug Log File".to_string(),
+                    kind: UciOptionType::String {
+                        default: String::new(),
+                    },
+                })
+            ))
         );
         assert_eq!(
             engine_message(CompleteStr(
                 "option name Threads type spin default 1 min 1 max 128"
             )),
-            Ok((CompleteStr(""), EngineMessage::UciOption))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::UciOption(UciOption {
+                    name: "Threads".to_string(),
+                    kind: UciOptionType::Spin {
+                        default: 1,
+                        min: 1,
+                        max: 128,
+                    },
+                })
+            ))
         );
         assert_eq!(
             engine_message(CompleteStr(
                 "option name Hash type spin default 16 min 1 max 1048576"
             )),
-            Ok((CompleteStr(""), EngineMessage::UciOption))
+            Ok((
+                CompleteStr(""),
+                EngineMessage::UciOption(UciOption {
+                    name: "Hash".to_string(),
+                    kind: UciOptionType::Spin {
+                        default: 16,
+                        min: 1,
+                        max: 1048576,
+                    },
+                })
+            ))
         );
     }
 
@@ -0,0 +1,472 @@
+use super::EngineMessage;
+use super::take_all;
+use nom::{digit, space, types::CompleteStr};
+use std::fmt;
+
+/// An option advertised by the engine during the `uci` handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciOption {
+    pub name: String,
+    pub kind: UciOptionType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciOptionType {
+    Check {
+        default: bool,
+    },
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Combo {
+        default: String,
+        vars: Vec<String>,
+    },
+    Button,
+    String {
+        default: String,
+    },
+}
+
+/// A value to assign to an option with `setoption`.
+///
+/// Buttons have no value and are pressed with `Engine::press_button`
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciOptionValue {
+    Check(bool),
+    Spin(i64),
+    Combo(String),
+    String(String),
+}
+
+impl fmt::Display for UciOptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UciOptionValue::Check(value) => write!(f, "{}", value),
+            UciOptionValue::Spin(value) => write!(f, "{}", value),
+            UciOptionValue::Combo(ref value) => write!(f, "{}", value),
+            UciOptionValue::String(ref value) if value.is_empty() => write!(f, "<empty>"),
+            UciOptionValue::String(ref value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Why a value was rejected before being sent to the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionError {
+    /// The engine did not advertise an option with this name.
+    UnknownOption(String),
+    /// The value does not have the type the engine advertised.
+    TypeMismatch(String),
+    OutOfRange {
+        name: String,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+    UnknownVar {
+        name: String,
+        var: String,
+    },
+    /// The name or value contains a line break, which would end the
+    /// "setoption" command early and send the rest as another command.
+    LineBreak(String),
+}
+
+/// Rejects a name or value that cannot be written on a single line.
+pub fn check_line_break(text: &str) -> Result<(), OptionError> {
+    if text.contains(&['\r', '\n'][..]) {
+        Err(OptionError::LineBreak(text.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+impl UciOption {
+    /// Checks that `value` is acceptable for this option according to the
+    /// declaration sent by the engine.
+    pub fn validate(&self, value: &UciOptionValue) -> Result<(), OptionError> {
+        match *value {
+            UciOptionValue::Combo(ref text) | UciOptionValue::String(ref text) => {
+                check_line_break(text)?
+            }
+            _ => {}
+        }
+
+        match (&self.kind, value) {
+            (&UciOptionType::Check { .. }, &UciOptionValue::Check(_)) => Ok(()),
+            (&UciOptionType::Spin { min, max, .. }, &UciOptionValue::Spin(value)) => {
+                if value < min || value > max {
+                    Err(OptionError::OutOfRange {
+                        name: self.name.clone(),
+                        value,
+                        min,
+                        max,
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+            (&UciOptionType::Combo { ref vars, .. }, &UciOptionValue::Combo(ref var)) => {
+                if vars.contains(var) {
+                    Ok(())
+                } else {
+                    Err(OptionError::UnknownVar {
+                        name: self.name.clone(),
+                        var: var.clone(),
+                    })
+                }
+            }
+            (&UciOptionType::String { .. }, &UciOptionValue::String(_)) => Ok(()),
+            _ => Err(OptionError::TypeMismatch(self.name.clone())),
+        }
+    }
+}
+
+named!(spin_value<CompleteStr, i64>, map_res!(
+    recognize!(pair!(opt!(tag!("-")), digit)),
+    | input: CompleteStr | input.0.parse::<i64>()
+));
+
+// A check option without a default is off.
+named!(check<CompleteStr, UciOptionType>, do_parse!(
+    tag!("check") >>
+    default: opt!(do_parse!(
+        space >>
+        tag!("default") >>
+        space >>
+        default: alt!(
+            do_parse!(tag!("true") >> (true)) |
+            do_parse!(tag!("false") >> (false))
+        ) >>
+        (default)
+    )) >>
+    (UciOptionType::Check { default: default.unwrap_or(false) })
+));
+
+named!(spin_field<CompleteStr, (CompleteStr, i64)>, do_parse!(
+    space >>
+    key: alt!(tag!("default") | tag!("min") | tag!("max")) >>
+    space >>
+    value: spin_value >>
+    ((key, value))
+));
+
+/// The default, min and max of a spin option can come in any order, but
+/// all three are required.
+fn spin_fields(fields: Vec<(CompleteStr, i64)>) -> Option<UciOptionType> {
+    let (mut default, mut min, mut max) = (None, None, None);
+    for (key, value) in fields {
+        match key.0 {
+            "default" => default = Some(value),
+            "min" => min = Some(value),
+            _ => max = Some(value),
+        }
+    }
+
+    Some(UciOptionType::Spin {
+        default: default?,
+        min: min?,
+        max: max?,
+    })
+}
+
+named!(spin<CompleteStr, UciOptionType>, map_opt!(
+    preceded!(tag!("spin"), many1!(spin_field)),
+    spin_fields
+));
+
+/// Combo values may contain spaces, so they are delimited by the `var`
+/// keyword rather than by whitespace. The input starts right after
+/// `default`, so that an empty default is followed by " var ".
+fn combo_values(input: CompleteStr) -> UciOptionType {
+    let mut values = input.0.split(" var ").map(|v| v.trim().to_string());
+    let default = values.next().unwrap_or_default();
+
+    UciOptionType::Combo {
+        default: default,
+        vars: values.collect(),
+    }
+}
+
+named!(combo<CompleteStr, UciOptionType>, do_parse!(
+    tag!("combo") >>
+    space >>
+    tag!("default") >>
+    values: take_all >>
+    (combo_values(values))
+));
+
+named!(button<CompleteStr, UciOptionType>, do_parse!(
+    tag!("button") >>
+    (UciOptionType::Button)
+));
+
+named!(string<CompleteStr, UciOptionType>, do_parse!(
+    tag!("string") >>
+    space >>
+    tag!("default") >>
+    default: take_all >>
+    ({
+        let default = default.0.trim();
+        UciOptionType::String {
+            default: if default == "<empty>" {
+                String::new()
+            } else {
+                default.to_string()
+            },
+        }
+    })
+));
+
+named!(option_type<CompleteStr, UciOptionType>, alt!(
+    check |
+    spin |
+    combo |
+    button |
+    string
+));
+
+named!(pub option<CompleteStr, EngineMessage>, do_parse!(
+    tag!("option") >>
+    space >>
+    tag!("name") >>
+    space >>
+    name: take_until_and_consume!(" type ") >>
+    kind: option_type >>
+    (EngineMessage::UciOption(UciOption {
+        name: name.0.trim().to_string(),
+        kind: kind
+    }))
+));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const EMPTY_SLICE: CompleteStr = CompleteStr("");
+
+    #[test]
+    fn validate_test() {
+        let hash = UciOption {
+            name: "Hash".to_string(),
+            kind: UciOptionType::Spin {
+                default: 16,
+                min: 1,
+                max: 1024,
+            },
+        };
+        assert_eq!(hash.validate(&UciOptionValue::Spin(128)), Ok(()));
+        assert_eq!(
+            hash.validate(&UciOptionValue::Spin(2048)),
+            Err(OptionError::OutOfRange {
+                name: "Hash".to_string(),
+                value: 2048,
+                min: 1,
+                max: 1024,
+            })
+        );
+        assert_eq!(
+            hash.validate(&UciOptionValue::Check(true)),
+            Err(OptionError::TypeMismatch("Hash".to_string()))
+        );
+
+        let style = UciOption {
+            name: "Style".to_string(),
+            kind: UciOptionType::Combo {
+                default: "Normal".to_string(),
+                vars: vec!["Solid".to_string(), "Normal".to_string()],
+            },
+        };
+        assert_eq!(
+            style.validate(&UciOptionValue::Combo("Solid".to_string())),
+            Ok(())
+        );
+        assert_eq!(
+            style.validate(&UciOptionValue::Combo("Risky".to_string())),
+            Err(OptionError::UnknownVar {
+                name: "Style".to_string(),
+                var: "Risky".to_string(),
+            })
+        );
+
+        let log_file = UciOption {
+            name: "Debug Log File".to_string(),
+            kind: UciOptionType::String {
+                default: String::new(),
+            },
+        };
+        assert_eq!(
+            log_file.validate(&UciOptionValue::String("log\nquit".to_string())),
+            Err(OptionError::LineBreak("log\nquit".to_string()))
+        );
+        assert!(check_line_break("Hash\r").is_err());
+        assert_eq!(check_line_break("Debug Log File"), Ok(()));
+    }
+
+    #[test]
+    fn value_display_test() {
+        assert_eq!(format!("{}", UciOptionValue::Check(true)), "true");
+        assert_eq!(format!("{}", UciOptionValue::Spin(-20)), "-20");
+        assert_eq!(
+            format!("{}", UciOptionValue::String(String::new())),
+            "<empty>"
+        );
+    }
+
+    #[test]
+    fn check_test() {
+        assert_eq!(
+            option(CompleteStr("option name Ponder type check default false")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Ponder".to_string(),
+                    kind: UciOptionType::Check { default: false },
+                })
+            ))
+        );
+        assert_eq!(
+            option(CompleteStr("option name Foo type check")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Foo".to_string(),
+                    kind: UciOptionType::Check { default: false },
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn spin_test() {
+        assert_eq!(
+            option(CompleteStr(
+                "option name Contempt type spin default 0 min -100 max 100"
+            )),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Contempt".to_string(),
+                    kind: UciOptionType::Spin {
+                        default: 0,
+                        min: -100,
+                        max: 100,
+                    },
+                })
+            ))
+        );
+        assert_eq!(
+            option(CompleteStr(
+                "option name Threads type spin min 1 max 512 default 1"
+            )),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Threads".to_string(),
+                    kind: UciOptionType::Spin {
+                        default: 1,
+                        min: 1,
+                        max: 512,
+                    },
+                })
+            ))
+        );
+        assert!(
+            option(CompleteStr("option name Threads type spin min 1 max 512")).is_err()
+        );
+    }
+
+    #[test]
+    fn combo_test() {
+        assert_eq!(
+            option(CompleteStr(
+                "option name Analysis Contempt type combo default Both var Off var White var Black var Both"
+            )),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Analysis Contempt".to_string(),
+                    kind: UciOptionType::Combo {
+                        default: "Both".to_string(),
+                        vars: vec![
+                            "Off".to_string(),
+                            "White".to_string(),
+                            "Black".to_string(),
+                            "Both".to_string(),
+                        ],
+                    },
+                })
+            ))
+        );
+        assert_eq!(
+            option(CompleteStr("option name Style type combo default  var A var B")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Style".to_string(),
+                    kind: UciOptionType::Combo {
+                        default: String::new(),
+                        vars: vec!["A".to_string(), "B".to_string()],
+                    },
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn button_test() {
+        assert_eq!(
+            option(CompleteStr("option name Clear Hash type button")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Clear Hash".to_string(),
+                    kind: UciOptionType::Button,
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn string_test() {
+        assert_eq!(
+            option(CompleteStr("option name Debug Log File type string default")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "Debug Log File".to_string(),
+                    kind: UciOptionType::String {
+                        default: String::new(),
+                    },
+                })
+            ))
+        );
+        assert_eq!(
+            option(CompleteStr("option name SyzygyPath type string default <empty>")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "SyzygyPath".to_string(),
+                    kind: UciOptionType::String {
+                        default: String::new(),
+                    },
+                })
+            ))
+        );
+        assert_eq!(
+            option(CompleteStr("option name EvalFile type string default nn-62ef826d1a6d.nnue")),
+            Ok((
+                EMPTY_SLICE,
+                EngineMessage::UciOption(UciOption {
+                    name: "EvalFile".to_string(),
+                    kind: UciOptionType::String {
+                        default: "nn-62ef826d1a6d.nnue".to_string(),
+                    },
+                })
+            ))
+        );
+    }
+}
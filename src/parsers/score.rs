@@ -0,0 +1,165 @@
+use super::signed_number;
+use nom::{space, types::CompleteStr};
+use std::cmp::Ordering;
+
+/// An evaluation from the engine's point of view.
+///
+/// Scores are totally ordered from worst to best for the engine: being
+/// mated sooner ranks below being mated later, which ranks below any
+/// centipawn score, which ranks below mating later, which ranks below
+/// mating sooner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32, Bound),
+    /// Mate in the given number of moves. A negative number means the
+    /// engine is getting mated.
+    Mate(i32, Bound),
+}
+
+/// Whether a score is exact or only a bound on the real evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bound {
+    Upper,
+    Exact,
+    Lower,
+}
+
+const MATE_VALUE: i64 = 1 << 40;
+
+impl Score {
+    pub fn bound(&self) -> Bound {
+        match *self {
+            Score::Centipawns(_, bound) | Score::Mate(_, bound) => bound,
+        }
+    }
+
+    fn sort_key(&self) -> i64 {
+        match *self {
+            Score::Centipawns(cp, _) => i64::from(cp),
+            Score::Mate(moves, _) if moves > 0 => MATE_VALUE - i64::from(moves),
+            Score::Mate(moves, _) => -MATE_VALUE - i64::from(moves),
+        }
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Score) -> Ordering {
+        self.sort_key()
+            .cmp(&other.sort_key())
+            .then(self.bound().cmp(&other.bound()))
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Score) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+named!(bound<CompleteStr, Bound>, alt!(
+    do_parse!(tag!("lowerbound") >> (Bound::Lower)) |
+    do_parse!(tag!("upperbound") >> (Bound::Upper))
+));
+
+named!(centipawns<CompleteStr, i32>, do_parse!(
+    tag!("cp") >>
+    space >>
+    value: signed_number >>
+    (value)
+));
+
+named!(mate<CompleteStr, i32>, do_parse!(
+    tag!("mate") >>
+    space >>
+    moves: signed_number >>
+    (moves)
+));
+
+named!(pub score<CompleteStr, Score>, do_parse!(
+    value: alt!(
+        map!(centipawns, |x| (x, false)) |
+        map!(mate, |x| (x, true))
+    ) >>
+    bound: opt!(preceded!(space, bound)) >>
+    ({
+        let bound = bound.unwrap_or(Bound::Exact);
+        match value {
+            (x, false) => Score::Centipawns(x, bound),
+            (x, true) => Score::Mate(x, bound),
+        }
+    })
+));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const EMPTY_SLICE: CompleteStr = CompleteStr("");
+
+    #[test]
+    fn centipawns_test() {
+        assert_eq!(
+            score(CompleteStr("cp 90")),
+            Ok((EMPTY_SLICE, Score::Centipawns(90, Bound::Exact)))
+        );
+        assert_eq!(
+            score(CompleteStr("cp -35")),
+            Ok((EMPTY_SLICE, Score::Centipawns(-35, Bound::Exact)))
+        );
+    }
+
+    #[test]
+    fn mate_test() {
+        assert_eq!(
+            score(CompleteStr("mate 3")),
+            Ok((EMPTY_SLICE, Score::Mate(3, Bound::Exact)))
+        );
+        assert_eq!(
+            score(CompleteStr("mate -2")),
+            Ok((EMPTY_SLICE, Score::Mate(-2, Bound::Exact)))
+        );
+    }
+
+    #[test]
+    fn bound_test() {
+        assert_eq!(
+            score(CompleteStr("cp 12 lowerbound")),
+            Ok((EMPTY_SLICE, Score::Centipawns(12, Bound::Lower)))
+        );
+        assert_eq!(
+            score(CompleteStr("mate 5 upperbound")),
+            Ok((EMPTY_SLICE, Score::Mate(5, Bound::Upper)))
+        );
+    }
+
+    #[test]
+    fn ordering_test() {
+        let mut scores = vec![
+            Score::Centipawns(900, Bound::Exact),
+            Score::Mate(-2, Bound::Exact),
+            Score::Mate(3, Bound::Exact),
+            Score::Centipawns(-900, Bound::Exact),
+            Score::Mate(1, Bound::Exact),
+            Score::Mate(-5, Bound::Exact),
+            Score::Mate(0, Bound::Exact),
+            Score::Centipawns(0, Bound::Exact),
+        ];
+        scores.sort();
+
+        assert_eq!(
+            scores,
+            vec![
+                Score::Mate(0, Bound::Exact),
+                Score::Mate(-2, Bound::Exact),
+                Score::Mate(-5, Bound::Exact),
+                Score::Centipawns(-900, Bound::Exact),
+                Score::Centipawns(0, Bound::Exact),
+                Score::Centipawns(900, Bound::Exact),
+                Score::Mate(3, Bound::Exact),
+                Score::Mate(1, Bound::Exact),
+            ]
+        );
+
+        assert!(Score::Centipawns(50, Bound::Upper) < Score::Centipawns(50, Bound::Exact));
+        assert!(Score::Centipawns(50, Bound::Lower) > Score::Centipawns(50, Bound::Exact));
+    }
+}
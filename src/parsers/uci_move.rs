@@ -1,13 +1,26 @@
-use nom::types::CompleteStr;
+use fen::{CastlingMode, CastlingSide, Fen};
+use nom::{alphanumeric, types::CompleteStr};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
     pub promotion_piece: Option<PromotionPiece>,
 }
 
+impl Move {
+    /// The side this move castles on in `fen`, if it is a castling move
+    /// written the way `mode` expects.
+    pub fn castling_side(&self, fen: &Fen, mode: CastlingMode) -> Option<CastlingSide> {
+        [CastlingSide::KingSide, CastlingSide::QueenSide]
+            .iter()
+            .cloned()
+            .find(|&side| fen.castling_move(side, mode) == Some(*self))
+    }
+}
+
 impl fmt::Display for Move {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(ref p) = self.promotion_piece {
@@ -18,7 +31,22 @@ impl fmt::Display for Move {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// The string that failed to parse as a move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError(pub String);
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Move, ParseMoveError> {
+        match uci_move(CompleteStr(s)) {
+            Ok((CompleteStr(""), m)) => Ok(m),
+            _ => Err(ParseMoveError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Square {
     pub file: File,
     pub rank: Rank,
@@ -30,7 +58,7 @@ impl fmt::Display for Square {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum File {
     A,
     B,
@@ -42,6 +70,28 @@ pub enum File {
     H,
 }
 
+impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    /// The file's index, from 0 for the a-file to 7 for the h-file.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<File> {
+        File::ALL.get(index).cloned()
+    }
+}
+
 impl fmt::Display for File {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let c = match *self {
@@ -58,7 +108,7 @@ impl fmt::Display for File {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Rank {
     First,
     Second,
@@ -70,6 +120,28 @@ pub enum Rank {
     Eight,
 }
 
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eight,
+    ];
+
+    /// The rank's index, from 0 for the first rank to 7 for the eighth.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Rank> {
+        Rank::ALL.get(index).cloned()
+    }
+}
+
 impl fmt::Display for Rank {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let c = match *self {
@@ -86,7 +158,7 @@ impl fmt::Display for Rank {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PromotionPiece {
     Knight,
     Bishop,
@@ -97,7 +169,7 @@ pub enum PromotionPiece {
 impl fmt::Display for PromotionPiece {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let c = match *self {
-            PromotionPiece::Knight => 'k',
+            PromotionPiece::Knight => 'n',
             PromotionPiece::Bishop => 'b',
             PromotionPiece::Rook => 'r',
             PromotionPiece::Queen => 'q',
@@ -110,6 +182,7 @@ named!(pub uci_move<CompleteStr, Move>, do_parse!(
     from: square >>
     to: square >>
     promo_piece: opt!(promotion_piece) >>
+    not!(alphanumeric) >>
     (Move {
         from: from,
         to: to,
@@ -164,7 +237,7 @@ named!(promotion_piece<CompleteStr, PromotionPiece>, map_opt!(
     take!(1),
     | input: CompleteStr | -> Option<PromotionPiece> {
         match input.as_bytes()[0] {
-            b'k' => Some(PromotionPiece::Knight),
+            b'n' => Some(PromotionPiece::Knight),
             b'b' => Some(PromotionPiece::Bishop),
             b'r' => Some(PromotionPiece::Rook),
             b'q' => Some(PromotionPiece::Queen),
@@ -215,6 +288,32 @@ mod tests {
                 }
             ))
         );
+
+        assert!(uci_move(CompleteStr("e7e8k")).is_err());
+        assert!(uci_move(CompleteStr("e2e4e5")).is_err());
+    }
+
+    #[test]
+    fn from_str_test() {
+        assert_eq!(
+            "e2e4".parse(),
+            Ok(Move {
+                from: Square {
+                    file: File::E,
+                    rank: Rank::Second,
+                },
+                to: Square {
+                    file: File::E,
+                    rank: Rank::Fourth,
+                },
+                promotion_piece: None,
+            })
+        );
+        assert_eq!(
+            "e2e4x".parse::<Move>(),
+            Err(ParseMoveError("e2e4x".to_string()))
+        );
+        assert_eq!("e2".parse::<Move>(), Err(ParseMoveError("e2".to_string())));
     }
 
     #[test]
@@ -288,9 +387,10 @@ mod tests {
     #[test]
     fn promotion_piece_test() {
         assert_eq!(
-            promotion_piece(CompleteStr("k")),
+            promotion_piece(CompleteStr("n")),
             Ok((EMPTY_SLICE, PromotionPiece::Knight))
         );
+        assert!(promotion_piece(CompleteStr("k")).is_err());
         assert_eq!(
             promotion_piece(CompleteStr("b")),
             Ok((EMPTY_SLICE, PromotionPiece::Bishop))
@@ -305,6 +405,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn index_test() {
+        for (i, f) in File::ALL.iter().enumerate() {
+            assert_eq!(f.index(), i);
+            assert_eq!(File::from_index(i), Some(*f));
+        }
+        for (i, r) in Rank::ALL.iter().enumerate() {
+            assert_eq!(r.index(), i);
+            assert_eq!(Rank::from_index(i), Some(*r));
+        }
+        assert_eq!(File::from_index(8), None);
+        assert_eq!(Rank::from_index(8), None);
+    }
+
     #[test]
     fn display_test() {
         let g6 = Square {
@@ -340,4 +454,60 @@ mod tests {
 
         assert_eq!(format!("{}", a2a1r), "a2a1r");
     }
+
+    fn all_squares() -> Vec<Square> {
+        let mut squares = Vec::new();
+        for &rank in Rank::ALL.iter() {
+            for &file in File::ALL.iter() {
+                squares.push(Square { file, rank });
+            }
+        }
+        squares
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let promotions = [
+            None,
+            Some(PromotionPiece::Knight),
+            Some(PromotionPiece::Bishop),
+            Some(PromotionPiece::Rook),
+            Some(PromotionPiece::Queen),
+        ];
+
+        // Every pair of squares with every promotion piece, which covers
+        // castling written as a king move and every promotion.
+        for &from in all_squares().iter() {
+            for &to in all_squares().iter() {
+                for &promotion_piece in promotions.iter() {
+                    let m = Move {
+                        from,
+                        to,
+                        promotion_piece,
+                    };
+                    let text = format!("{}", m);
+                    assert_eq!(uci_move(CompleteStr(&text)), Ok((EMPTY_SLICE, m)));
+                    assert_eq!(text.parse(), Ok(m));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn knight_promotion_test() {
+        let e7e8n = Move {
+            from: Square {
+                file: File::E,
+                rank: Rank::Seventh,
+            },
+            to: Square {
+                file: File::E,
+                rank: Rank::Eight,
+            },
+            promotion_piece: Some(PromotionPiece::Knight),
+        };
+
+        assert_eq!(format!("{}", e7e8n), "e7e8n");
+        assert_eq!(uci_move(CompleteStr("e7e8n")), Ok((EMPTY_SLICE, e7e8n)));
+    }
 }
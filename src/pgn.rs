@@ -0,0 +1,860 @@
+use board::{Board, BoardError, Outcome};
+use fen::{CastlingMode, Color, Fen, FenError};
+use parsers::{Bound, Move, Score};
+use position::Position;
+use san::SanError;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use EngineId;
+
+/// The tags every PGN game starts with, in the order they are written.
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// PGN lines are kept under 80 characters.
+const MAX_LINE_LENGTH: usize = 79;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game is still going on, or was abandoned.
+    Unfinished,
+}
+
+impl From<Outcome> for GameResult {
+    fn from(outcome: Outcome) -> GameResult {
+        match outcome {
+            Outcome::Checkmate {
+                winner: Color::White,
+            } => GameResult::WhiteWins,
+            Outcome::Checkmate {
+                winner: Color::Black,
+            } => GameResult::BlackWins,
+            _ => GameResult::Draw,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = match *self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unfinished => "*",
+        };
+        write!(f, "{}", result)
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<GameResult, ()> {
+        match s {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Unfinished),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Why a PGN file could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    /// The text around the given token is not valid PGN, for example an
+    /// unterminated tag, comment or variation.
+    Syntax(String),
+    /// The "FEN" tag does not describe a valid position.
+    Fen(FenError),
+    /// A move of the mainline is not legal, or not valid SAN.
+    San(SanError),
+}
+
+impl From<FenError> for PgnError {
+    fn from(e: FenError) -> PgnError {
+        PgnError::Fen(e)
+    }
+}
+
+impl From<SanError> for PgnError {
+    fn from(e: SanError) -> PgnError {
+        PgnError::San(e)
+    }
+}
+
+impl From<BoardError> for PgnError {
+    fn from(e: BoardError) -> PgnError {
+        match e {
+            BoardError::InvalidFen(e) => PgnError::Fen(e),
+            BoardError::IllegalMove { illegal_move, .. } => {
+                PgnError::San(SanError::Illegal(illegal_move.to_string()))
+            }
+        }
+    }
+}
+
+/// A move of a game, with what gets written in the comment after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub played: Move,
+    pub san: String,
+    /// The last score the engine reported before playing the move, from its
+    /// own point of view as in "info score". It is written from White's
+    /// point of view in the `[%eval]` comment.
+    pub eval: Option<Score>,
+    /// The time left on the mover's clock after the move, written in the
+    /// `[%clk]` comment.
+    pub clock: Option<Duration>,
+    /// The time spent on the move, written in the `[%emt]` comment.
+    pub elapsed: Option<Duration>,
+}
+
+/// A game that can be written as PGN.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::{GameResult, PgnGame};
+///
+/// let mut game = PgnGame::new();
+/// game.set_tag("Event", "Test match");
+/// game.push("e2e4".parse().unwrap()).unwrap();
+/// game.set_result(GameResult::Unfinished);
+///
+/// assert!(game.to_string().ends_with("\n1. e4 *\n"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    start: Board,
+    board: Board,
+    moves: Vec<PgnMove>,
+    result: GameResult,
+}
+
+impl PgnGame {
+    /// A game from the standard starting position, with every tag of the
+    /// Seven Tag Roster unknown.
+    pub fn new() -> PgnGame {
+        PgnGame::with_board(Board::new())
+    }
+
+    /// A game from a custom position, which adds the "SetUp" and "FEN"
+    /// tags.
+    pub fn from_fen(fen: Fen) -> Result<PgnGame, BoardError> {
+        let mut game = PgnGame::with_board(Board::from_fen(fen.clone())?);
+        game.set_tag("SetUp", "1");
+        game.set_tag("FEN", &fen.to_string());
+        Ok(game)
+    }
+
+    /// A game made of the moves of a position sent with
+    /// `Engine::set_position`. Castling moves are read in `mode`.
+    pub fn from_position(position: &Position, mode: CastlingMode) -> Result<PgnGame, BoardError> {
+        let mut game = match *position {
+            Position::StartPos { .. } => PgnGame::new(),
+            Position::Fen { ref fen, .. } => PgnGame::from_fen(fen.clone())?,
+        };
+        game.start.set_castling_mode(mode);
+        game.board.set_castling_mode(mode);
+
+        for &m in position.moves() {
+            game.push(m)?;
+        }
+
+        Ok(game)
+    }
+
+    fn with_board(board: Board) -> PgnGame {
+        let date = "????.??.??".to_string();
+        let tags = SEVEN_TAG_ROSTER[..6]
+            .iter()
+            .map(|&name| {
+                let value = if name == "Date" { date.clone() } else { "?".to_string() };
+                (name.to_string(), value)
+            })
+            .collect();
+
+        PgnGame {
+            tags,
+            start: board.clone(),
+            board,
+            moves: Vec::new(),
+            result: GameResult::Unfinished,
+        }
+    }
+
+    /// Sets a tag, replacing its previous value. Setting "Result" is the
+    /// same as calling `set_result`.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        if name == "Result" {
+            if let Ok(result) = value.parse() {
+                self.result = result;
+            }
+            return;
+        }
+
+        match self.tags.iter_mut().find(|tag| tag.0 == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|tag| tag.0 == name)
+            .map(|tag| tag.1.as_str())
+    }
+
+    /// Every tag except "Result", in the order they are written.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Fills in "White" and "Black" with the names the engines sent
+    /// during the handshake.
+    pub fn set_players(&mut self, white: &EngineId, black: &EngineId) {
+        let name = |id: &EngineId| id.name.clone().unwrap_or_else(|| "?".to_string());
+        self.set_tag("White", &name(white));
+        self.set_tag("Black", &name(black));
+    }
+
+    /// Sets the "TimeControl" tag, such as "40/5400:1800" or "300+2".
+    pub fn set_time_control(&mut self, time_control: &str) {
+        self.set_tag("TimeControl", time_control);
+    }
+
+    pub fn set_result(&mut self, result: GameResult) {
+        self.result = result;
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    /// Plays a move without any comment.
+    pub fn push(&mut self, m: Move) -> Result<(), BoardError> {
+        self.push_annotated(m, None, None, None)
+    }
+
+    /// Plays a move, followed by `[%eval]`, `[%clk]` and `[%emt]` comments
+    /// for the values that are given.
+    pub fn push_annotated(
+        &mut self,
+        m: Move,
+        eval: Option<Score>,
+        clock: Option<Duration>,
+        elapsed: Option<Duration>,
+    ) -> Result<(), BoardError> {
+        let san = self.board.to_san(m)?;
+        self.board.play(m)?;
+        self.moves.push(PgnMove {
+            played: m,
+            san,
+            eval,
+            clock,
+            elapsed,
+        });
+
+        Ok(())
+    }
+
+    pub fn moves(&self) -> &[PgnMove] {
+        &self.moves
+    }
+
+    /// The position the game started from.
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// The position after the last move.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Every position of the game, from the starting one to the one after
+    /// the last move, ready to be sent with `Engine::set_position`.
+    pub fn positions(&self) -> Vec<Position> {
+        let mut position = if self.tag("FEN").is_some() {
+            Position::fen(self.start.fen().clone())
+        } else {
+            Position::startpos()
+        };
+
+        let mut positions = vec![position.clone()];
+        for m in &self.moves {
+            position.push(m.played);
+            positions.push(position.clone());
+        }
+
+        positions
+    }
+
+    /// Reads every game of a PGN file.
+    ///
+    /// Only the mainline is kept: variations and NAGs are skipped, and
+    /// comments are only read for their `[%eval]`, `[%clk]` and `[%emt]`
+    /// commands.
+    pub fn parse_all(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let mut reader = Reader { text, pos: 0 };
+        let mut games = Vec::new();
+
+        loop {
+            reader.skip_whitespace();
+            if reader.peek().is_none() {
+                return Ok(games);
+            }
+            games.push(reader.game()?);
+        }
+    }
+}
+
+/// Reads a single game, as `PgnGame::parse_all` does.
+impl FromStr for PgnGame {
+    type Err = PgnError;
+
+    fn from_str(s: &str) -> Result<PgnGame, PgnError> {
+        let mut games = PgnGame::parse_all(s)?;
+        if games.len() != 1 {
+            return Err(PgnError::Syntax(s.to_string()));
+        }
+
+        Ok(games.remove(0))
+    }
+}
+
+/// Characters that end a SAN token.
+const DELIMITERS: &str = "{}()[];$";
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().map_or(false, &predicate) {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn syntax_error(&self) -> PgnError {
+        let rest = &self.text[self.pos..];
+        let token = rest.split_whitespace().next().unwrap_or(rest);
+        PgnError::Syntax(token.to_string())
+    }
+
+    /// Skips whitespace, and lines starting with the "%" escape.
+    fn skip_whitespace(&mut self) {
+        loop {
+            self.take_while(char::is_whitespace);
+            let line_start = self.pos == 0 || self.text[..self.pos].ends_with('\n');
+            if line_start && self.peek() == Some('%') {
+                self.take_while(|c| c != '\n');
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn tag_pair(&mut self) -> Result<(String, String), PgnError> {
+        self.bump();
+        self.skip_whitespace();
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '_').to_string();
+        self.skip_whitespace();
+        if name.is_empty() || self.bump() != Some('"') {
+            return Err(self.syntax_error());
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\\') => value.extend(self.bump()),
+                Some('"') => break,
+                Some(c) => value.push(c),
+                None => return Err(self.syntax_error()),
+            }
+        }
+
+        self.skip_whitespace();
+        if self.bump() != Some(']') {
+            return Err(self.syntax_error());
+        }
+
+        Ok((name, value))
+    }
+
+    fn comment(&mut self) -> Result<&'a str, PgnError> {
+        self.bump();
+        let comment = self.take_while(|c| c != '}');
+        if self.bump().is_none() {
+            return Err(self.syntax_error());
+        }
+
+        Ok(comment)
+    }
+
+    /// Skips a variation, along with the variations nested in it.
+    fn variation(&mut self) -> Result<(), PgnError> {
+        self.bump();
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.peek() {
+                Some('(') => depth += 1,
+                Some(')') => depth -= 1,
+                Some('{') => {
+                    self.comment()?;
+                    continue;
+                }
+                Some(';') => {
+                    self.take_while(|c| c != '\n');
+                    continue;
+                }
+                Some(_) => {}
+                None => return Err(self.syntax_error()),
+            }
+            self.bump();
+        }
+
+        Ok(())
+    }
+
+    fn game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut tags = Vec::new();
+        while self.peek() == Some('[') {
+            tags.push(self.tag_pair()?);
+            self.skip_whitespace();
+        }
+
+        let mut game = match tags.iter().find(|tag| tag.0 == "FEN") {
+            Some(tag) => PgnGame::from_fen(tag.1.parse()?)?,
+            None => PgnGame::new(),
+        };
+        for &(ref name, ref value) in &tags {
+            game.set_tag(name, value);
+        }
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                // A game without a result ends where the next one starts.
+                None | Some('[') => return Ok(game),
+                Some('{') => {
+                    let comment = self.comment()?;
+                    game.read_comment(comment);
+                }
+                Some(';') => {
+                    self.take_while(|c| c != '\n');
+                }
+                Some('(') => self.variation()?,
+                Some('$') => {
+                    self.bump();
+                    self.take_while(|c| c.is_ascii_digit());
+                }
+                Some(_) => {
+                    let token =
+                        self.take_while(|c| !c.is_whitespace() && !DELIMITERS.contains(c));
+                    if token.is_empty() {
+                        return Err(self.syntax_error());
+                    }
+                    if let Ok(result) = token.parse() {
+                        game.set_result(result);
+                        return Ok(game);
+                    }
+
+                    // Move numbers can be glued to the move, as in "1.e4".
+                    let san = match token.rfind('.') {
+                        Some(i) => &token[i + 1..],
+                        None => token,
+                    };
+                    if !san.is_empty() {
+                        let m = game.board.parse_san(san)?;
+                        game.push(m)?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The argument of a command such as `[%clk 0:04:58]` in a comment.
+fn command<'a>(comment: &'a str, name: &str) -> Option<&'a str> {
+    let tag = format!("[%{} ", name);
+    let start = comment.find(&tag)? + tag.len();
+    let end = start + comment[start..].find(']')?;
+
+    Some(comment[start..end].trim())
+}
+
+/// Reads an `[%eval]` value, given from White's point of view, as a score
+/// from the point of view of the side that moved.
+fn parse_eval(value: &str, mover: Color) -> Option<Score> {
+    let sign = match mover {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+
+    if value.starts_with('#') {
+        let moves: i32 = value[1..].parse().ok()?;
+        Some(Score::Mate(moves * sign, Bound::Exact))
+    } else {
+        let pawns: f64 = value.parse().ok()?;
+        Some(Score::Centipawns((pawns * 100.0).round() as i32 * sign, Bound::Exact))
+    }
+}
+
+/// Reads a `[%clk]` or `[%emt]` value such as "1:05:30" or "0:00:09.5".
+fn parse_clock(value: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        let part: f64 = part.parse().ok()?;
+        seconds = seconds * 60.0 + part;
+    }
+    if seconds < 0.0 {
+        return None;
+    }
+
+    let millis = (seconds * 1000.0).round() as u64;
+    Some(Duration::from_millis(millis))
+}
+
+impl PgnGame {
+    /// Attaches the `[%eval]`, `[%clk]` and `[%emt]` commands of a comment
+    /// to the last move.
+    fn read_comment(&mut self, comment: &str) {
+        let mover = self.board.side_to_move().opposite();
+        let last = match self.moves.last_mut() {
+            Some(last) => last,
+            None => return,
+        };
+
+        if let Some(eval) = command(comment, "eval").and_then(|v| parse_eval(v, mover)) {
+            last.eval = Some(eval);
+        }
+        if let Some(clock) = command(comment, "clk").and_then(parse_clock) {
+            last.clock = Some(clock);
+        }
+        if let Some(elapsed) = command(comment, "emt").and_then(parse_clock) {
+            last.elapsed = Some(elapsed);
+        }
+    }
+}
+
+impl Default for PgnGame {
+    fn default() -> PgnGame {
+        PgnGame::new()
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes a score in pawns from White's point of view, as `[%eval]` expects.
+fn eval(score: Score, mover: Color) -> String {
+    let sign = match mover {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+
+    match score {
+        Score::Centipawns(cp, _) => format!("{:.2}", f64::from(cp * sign) / 100.0),
+        Score::Mate(moves, _) => format!("#{}", moves * sign),
+    }
+}
+
+fn clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Same as `clock`, but keeps the milliseconds, since engines often spend
+/// less than a second on a move.
+fn elapsed(time: Duration) -> String {
+    let millis = time.subsec_nanos() / 1_000_000;
+    if millis == 0 {
+        clock(time)
+    } else {
+        format!("{}.{:03}", clock(time), millis)
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(ref name, ref value) in self.tags.iter().take(6) {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        writeln!(f, "[Result \"{}\"]", self.result)?;
+        for &(ref name, ref value) in self.tags.iter().skip(6) {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        f.write_str("\n")?;
+
+        let mut tokens = Vec::new();
+        let mut mover = self.start.side_to_move();
+        let mut number = self.start.fen().fullmove_number;
+        let mut after_comment = true;
+
+        for m in &self.moves {
+            // Move numbers stay on the same line as their move.
+            tokens.push(match mover {
+                Color::White => format!("{}. {}", number, m.san),
+                Color::Black if after_comment => format!("{}... {}", number, m.san),
+                Color::Black => m.san.clone(),
+            });
+
+            let mut comments = Vec::new();
+            if let Some(score) = m.eval {
+                comments.push(format!("[%eval {}]", eval(score, mover)));
+            }
+            if let Some(time) = m.clock {
+                comments.push(format!("[%clk {}]", clock(time)));
+            }
+            if let Some(time) = m.elapsed {
+                comments.push(format!("[%emt {}]", elapsed(time)));
+            }
+            after_comment = !comments.is_empty();
+            if after_comment {
+                tokens.push(format!("{{{}}}", comments.join(" ")));
+            }
+
+            if mover == Color::Black {
+                number += 1;
+            }
+            mover = mover.opposite();
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(game: &mut PgnGame, moves: &str) {
+        for m in moves.split_whitespace() {
+            game.push(m.parse().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn seven_tag_roster_test() {
+        let mut game = PgnGame::new();
+        game.set_tag("Event", "Engine \"match\"");
+        game.set_players(
+            &EngineId {
+                name: Some("Stockfish 9".to_string()),
+                author: None,
+            },
+            &EngineId::default(),
+        );
+        game.set_time_control("300+2");
+        push(&mut game, "f2f3 e7e5 g2g4 d8h4");
+        game.set_result(GameResult::BlackWins);
+
+        assert_eq!(
+            game.to_string(),
+            "[Event \"Engine \\\"match\\\"\"]\n\
+             [Site \"?\"]\n\
+             [Date \"????.??.??\"]\n\
+             [Round \"?\"]\n\
+             [White \"Stockfish 9\"]\n\
+             [Black \"?\"]\n\
+             [Result \"0-1\"]\n\
+             [TimeControl \"300+2\"]\n\
+             \n\
+             1. f3 e5 2. g4 Qh4# 0-1\n"
+        );
+    }
+
+    #[test]
+    fn comments_test() {
+        let mut game = PgnGame::new();
+        game.push_annotated(
+            "e2e4".parse().unwrap(),
+            Some(Score::Centipawns(35, Bound::Exact)),
+            Some(Duration::from_secs(298)),
+            Some(Duration::from_millis(1_250)),
+        ).unwrap();
+        game.push_annotated(
+            "e7e5".parse().unwrap(),
+            Some(Score::Centipawns(-20, Bound::Exact)),
+            Some(Duration::from_millis(3_661_500)),
+            None,
+        ).unwrap();
+        game.push_annotated(
+            "g1f3".parse().unwrap(),
+            Some(Score::Mate(-3, Bound::Exact)),
+            None,
+            None,
+        ).unwrap();
+        game.push("b8c6".parse().unwrap()).unwrap();
+
+        let pgn = game.to_string();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert_eq!(
+            movetext,
+            "1. e4 {[%eval 0.35] [%clk 0:04:58] [%emt 0:00:01.250]} 1... e5\n\
+             {[%eval 0.20] [%clk 1:01:01]} 2. Nf3 {[%eval #-3]} 2... Nc6 *\n"
+        );
+    }
+
+    #[test]
+    fn from_position_test() {
+        let fen: Fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30".parse().unwrap();
+        let mut position = Position::fen(fen);
+        position.push("e8d7".parse().unwrap());
+        position.push("e2e4".parse().unwrap());
+
+        let game = PgnGame::from_position(&position, CastlingMode::Standard).unwrap();
+        assert_eq!(game.tag("SetUp"), Some("1"));
+        assert_eq!(game.tag("FEN"), Some("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30"));
+        assert!(game.to_string().ends_with("\n\n30... Kd7 31. e4 *\n"));
+    }
+
+    #[test]
+    fn import_test() {
+        let pgn = "[Event \"Casual \\\"blitz\\\"\"]\n\
+                   [Site \"?\"]\n\
+                   [Annotator \"me\"]\n\
+                   \n\
+                   % An escaped line\n\
+                   1. e4 $1 {Best by test} e5?! (1... c5 {Sicilian (open)} 2. Nf3 (2. c3))\n\
+                   2.Nf3 ; a rest-of-line comment\n\
+                   Nc6 3. Bb5 a6 {[%eval 0.25] [%clk 0:02:59.5]} 1/2-1/2\n\
+                   \n\
+                   [Event \"Second\"]\n\
+                   \n\
+                   1. d4 *\n";
+
+        let games = PgnGame::parse_all(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let game = &games[0];
+        assert_eq!(game.tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!(game.tag("Annotator"), Some("me"));
+        assert_eq!(game.result(), GameResult::Draw);
+        let moves: Vec<&str> = game.moves().iter().map(|m| m.san.as_str()).collect();
+        assert_eq!(moves, vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(
+            game.moves()[5].eval,
+            Some(Score::Centipawns(-25, Bound::Exact))
+        );
+        assert_eq!(game.moves()[5].clock, Some(Duration::from_millis(179_500)));
+
+        assert_eq!(games[1].tag("Event"), Some("Second"));
+        assert_eq!(games[1].result(), GameResult::Unfinished);
+        assert_eq!(games[1].moves().len(), 1);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut game = PgnGame::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30".parse().unwrap())
+            .unwrap();
+        game.set_tag("Event", "Round trip");
+        game.push_annotated(
+            "e8d7".parse().unwrap(),
+            Some(Score::Mate(-12, Bound::Exact)),
+            Some(Duration::from_secs(61)),
+            Some(Duration::from_millis(2_500)),
+        ).unwrap();
+        game.push_annotated(
+            "e2e4".parse().unwrap(),
+            Some(Score::Centipawns(412, Bound::Exact)),
+            None,
+            Some(Duration::from_secs(3)),
+        ).unwrap();
+        game.set_result(GameResult::WhiteWins);
+
+        assert_eq!(game.to_string().parse(), Ok(game));
+    }
+
+    #[test]
+    fn positions_test() {
+        let game: PgnGame = "1. e4 e5 *".parse().unwrap();
+        let positions: Vec<String> = game.positions().iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            positions,
+            vec!["startpos", "startpos moves e2e4", "startpos moves e2e4 e7e5"]
+        );
+
+        let game: PgnGame = "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. e4 *"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            game.positions()[1].to_string(),
+            "fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 moves e2e4"
+        );
+    }
+
+    #[test]
+    fn import_error_test() {
+        assert_eq!(
+            PgnGame::parse_all("1. e4 e4 *"),
+            Err(PgnError::San(SanError::Illegal("e4".to_string())))
+        );
+        assert_eq!(
+            PgnGame::parse_all("[Event \"Unterminated]"),
+            Err(PgnError::Syntax("".to_string()))
+        );
+        assert_eq!(
+            PgnGame::parse_all("1. e4 (1. d4 *"),
+            Err(PgnError::Syntax("".to_string()))
+        );
+        assert_eq!(
+            PgnGame::parse_all("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"] *"),
+            Err(PgnError::Fen(FenError::InvalidKings))
+        );
+    }
+
+    #[test]
+    fn result_test() {
+        assert_eq!(
+            GameResult::from(Outcome::Checkmate {
+                winner: Color::White,
+            }),
+            GameResult::WhiteWins
+        );
+        assert_eq!(GameResult::from(Outcome::Stalemate), GameResult::Draw);
+        assert_eq!("1/2-1/2".parse(), Ok(GameResult::Draw));
+        assert_eq!(GameResult::Draw.to_string(), "1/2-1/2");
+    }
+}
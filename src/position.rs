@@ -0,0 +1,142 @@
+use fen::{Color, Fen};
+use parsers::Move;
+use std::fmt;
+
+/// The argument of a "position" command.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_uci::Position;
+///
+/// let position = Position::startpos();
+/// assert_eq!(position.to_string(), "startpos");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Position {
+    StartPos { moves: Vec<Move> },
+    Fen { fen: Fen, moves: Vec<Move> },
+}
+
+impl Position {
+    pub fn startpos() -> Position {
+        Position::StartPos { moves: Vec::new() }
+    }
+
+    pub fn fen(fen: Fen) -> Position {
+        Position::Fen {
+            fen,
+            moves: Vec::new(),
+        }
+    }
+
+    /// The moves played since the initial position.
+    pub fn moves(&self) -> &[Move] {
+        match *self {
+            Position::StartPos { ref moves } | Position::Fen { ref moves, .. } => moves,
+        }
+    }
+
+    /// The side to move once all the moves are played.
+    pub fn side_to_move(&self) -> Color {
+        let first = match *self {
+            Position::StartPos { .. } => Color::White,
+            Position::Fen { ref fen, .. } => fen.side_to_move,
+        };
+
+        if self.moves().len() % 2 == 0 {
+            first
+        } else {
+            first.opposite()
+        }
+    }
+
+    /// Appends a move, typically the one just played in the game.
+    pub fn push(&mut self, m: Move) {
+        match *self {
+            Position::StartPos { ref mut moves } | Position::Fen { ref mut moves, .. } => {
+                moves.push(m)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Position::StartPos { .. } => write!(f, "startpos")?,
+            Position::Fen { ref fen, .. } => write!(f, "fen {}", fen)?,
+        }
+
+        let moves = self.moves();
+        if !moves.is_empty() {
+            write!(f, " moves")?;
+            for m in moves {
+                write!(f, " {}", m)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsers::{File, PromotionPiece, Rank, Square};
+
+    #[test]
+    fn display_test() {
+        let mut position = Position::startpos();
+        position.push(Move {
+            from: Square {
+                file: File::E,
+                rank: Rank::Second,
+            },
+            to: Square {
+                file: File::E,
+                rank: Rank::Fourth,
+            },
+            promotion_piece: None,
+        });
+        position.push(Move {
+            from: Square {
+                file: File::E,
+                rank: Rank::Seventh,
+            },
+            to: Square {
+                file: File::E,
+                rank: Rank::Fifth,
+            },
+            promotion_piece: None,
+        });
+
+        assert_eq!(position.to_string(), "startpos moves e2e4 e7e5");
+        assert_eq!(position.side_to_move(), Color::White);
+    }
+
+    #[test]
+    fn fen_test() {
+        let fen = "8/P7/8/8/8/8/8/k6K w - - 0 1".parse().unwrap();
+        let mut position = Position::fen(fen);
+        assert_eq!(position.to_string(), "fen 8/P7/8/8/8/8/8/k6K w - - 0 1");
+        assert_eq!(position.side_to_move(), Color::White);
+
+        position.push(Move {
+            from: Square {
+                file: File::A,
+                rank: Rank::Seventh,
+            },
+            to: Square {
+                file: File::A,
+                rank: Rank::Eight,
+            },
+            promotion_piece: Some(PromotionPiece::Queen),
+        });
+        assert_eq!(
+            position.to_string(),
+            "fen 8/P7/8/8/8/8/8/k6K w - - 0 1 moves a7a8q"
+        );
+        assert_eq!(position.side_to_move(), Color::Black);
+    }
+}
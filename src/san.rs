@@ -0,0 +1,360 @@
+use board::{Board, BoardError};
+use fen::{CastlingSide, PieceKind};
+use parsers::{File, Move, PromotionPiece, Rank, Square};
+
+/// A SAN string that does not describe exactly one legal move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// The string is not valid SAN.
+    Invalid(String),
+    /// No legal move matches the string.
+    Illegal(String),
+    /// Several legal moves match the string.
+    Ambiguous(String),
+}
+
+fn piece_letter(kind: PieceKind) -> Option<char> {
+    match kind {
+        PieceKind::Pawn => None,
+        PieceKind::Knight => Some('N'),
+        PieceKind::Bishop => Some('B'),
+        PieceKind::Rook => Some('R'),
+        PieceKind::Queen => Some('Q'),
+        PieceKind::King => Some('K'),
+    }
+}
+
+fn piece_from_letter(c: char) -> Option<PieceKind> {
+    match c {
+        'N' => Some(PieceKind::Knight),
+        'B' => Some(PieceKind::Bishop),
+        'R' => Some(PieceKind::Rook),
+        'Q' => Some(PieceKind::Queen),
+        'K' => Some(PieceKind::King),
+        _ => None,
+    }
+}
+
+fn promotion_letter(piece: PromotionPiece) -> char {
+    match piece {
+        PromotionPiece::Knight => 'N',
+        PromotionPiece::Bishop => 'B',
+        PromotionPiece::Rook => 'R',
+        PromotionPiece::Queen => 'Q',
+    }
+}
+
+fn promotion_from_letter(c: char) -> Option<PromotionPiece> {
+    match c.to_ascii_uppercase() {
+        'N' => Some(PromotionPiece::Knight),
+        'B' => Some(PromotionPiece::Bishop),
+        'R' => Some(PromotionPiece::Rook),
+        'Q' => Some(PromotionPiece::Queen),
+        _ => None,
+    }
+}
+
+fn file_from_char(c: char) -> Option<File> {
+    if c >= 'a' && c <= 'h' {
+        File::from_index((c as u8 - b'a') as usize)
+    } else {
+        None
+    }
+}
+
+fn rank_from_char(c: char) -> Option<Rank> {
+    if c >= '1' && c <= '8' {
+        Rank::from_index((c as u8 - b'1') as usize)
+    } else {
+        None
+    }
+}
+
+/// Writes a move that is not castling, without the check suffix.
+fn describe(board: &Board, m: Move) -> String {
+    let piece = board.piece_at(m.from).expect("no piece on the move's origin");
+    let is_pawn = piece.kind == PieceKind::Pawn;
+    let capture = board.piece_at(m.to).is_some() || (is_pawn && m.from.file != m.to.file);
+    let mut san = String::new();
+
+    match piece_letter(piece.kind) {
+        None if capture => san.push_str(&m.from.file.to_string()),
+        None => {}
+        Some(letter) => {
+            san.push(letter);
+
+            // Other pieces of the same kind that can reach the same square.
+            let rivals: Vec<Square> = board
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to == m.to && other.from != m.from
+                        && board.piece_at(other.from).map(|p| p.kind) == Some(piece.kind)
+                })
+                .map(|other| other.from)
+                .collect();
+
+            if !rivals.is_empty() {
+                if rivals.iter().all(|s| s.file != m.from.file) {
+                    san.push_str(&m.from.file.to_string());
+                } else if rivals.iter().all(|s| s.rank != m.from.rank) {
+                    san.push_str(&m.from.rank.to_string());
+                } else {
+                    san.push_str(&m.from.to_string());
+                }
+            }
+        }
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&m.to.to_string());
+    if let Some(piece) = m.promotion_piece {
+        san.push('=');
+        san.push(promotion_letter(piece));
+    }
+
+    san
+}
+
+impl Board {
+    /// Writes a legal move in Standard Algebraic Notation, such as "Nf3",
+    /// "exd5", "O-O" or "e8=Q+".
+    pub fn to_san(&self, m: Move) -> Result<String, BoardError> {
+        let mut after = self.clone();
+        after.play(m)?;
+
+        let mut san = match m.castling_side(self.fen(), self.castling_mode()) {
+            Some(CastlingSide::KingSide) => "O-O".to_string(),
+            Some(CastlingSide::QueenSide) => "O-O-O".to_string(),
+            None => describe(self, m),
+        };
+        if after.is_checkmate() {
+            san.push('#');
+        } else if after.is_check() {
+            san.push('+');
+        }
+
+        Ok(san)
+    }
+
+    /// Reads a move in Standard Algebraic Notation.
+    ///
+    /// The check suffix and annotations such as "!?" are ignored, and
+    /// castling can also be written with zeros, as in "0-0".
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let trimmed = san.trim_right_matches(|c| "+#!?".contains(c));
+        let castling = match trimmed {
+            "O-O" | "0-0" => Some(CastlingSide::KingSide),
+            "O-O-O" | "0-0-0" => Some(CastlingSide::QueenSide),
+            _ => None,
+        };
+        if let Some(side) = castling {
+            return match self.fen().castling_move(side, self.castling_mode()) {
+                Some(m) if self.is_legal(m) => Ok(m),
+                _ => Err(SanError::Illegal(san.to_string())),
+            };
+        }
+
+        let mut chars: Vec<char> = trimmed
+            .chars()
+            .filter(|&c| c != 'x' && c != '-' && c != '=')
+            .collect();
+        let kind = match chars.first().and_then(|&c| piece_from_letter(c)) {
+            Some(kind) => {
+                chars.remove(0);
+                kind
+            }
+            None => PieceKind::Pawn,
+        };
+        let promotion = match chars.last().and_then(|&c| promotion_from_letter(c)) {
+            Some(piece) if kind == PieceKind::Pawn => {
+                chars.pop();
+                Some(piece)
+            }
+            _ => None,
+        };
+
+        let invalid = || SanError::Invalid(san.to_string());
+        if chars.len() < 2 || chars.len() > 4 {
+            return Err(invalid());
+        }
+        let (hint, to) = chars.split_at(chars.len() - 2);
+        let to = Square {
+            file: file_from_char(to[0]).ok_or_else(invalid)?,
+            rank: rank_from_char(to[1]).ok_or_else(invalid)?,
+        };
+
+        // What is left before the destination disambiguates the origin.
+        let mut file = None;
+        let mut rank = None;
+        for &c in hint {
+            if let Some(f) = file_from_char(c) {
+                file = Some(f);
+            } else if let Some(r) = rank_from_char(c) {
+                rank = Some(r);
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        let candidates: Vec<Move> = self.legal_moves()
+            .into_iter()
+            .filter(|m| {
+                m.to == to && m.promotion_piece == promotion
+                    && self.piece_at(m.from).map(|p| p.kind) == Some(kind)
+                    && file.map_or(true, |f| m.from.file == f)
+                    && rank.map_or(true, |r| m.from.rank == r)
+                    && m.castling_side(self.fen(), self.castling_mode()).is_none()
+            })
+            .collect();
+
+        match candidates.len() {
+            0 => Err(SanError::Illegal(san.to_string())),
+            1 => Ok(candidates[0]),
+            _ => Err(SanError::Ambiguous(san.to_string())),
+        }
+    }
+
+    /// Writes a sequence of moves played one after the other, such as a
+    /// principal variation, in SAN.
+    pub fn to_san_line(&self, moves: &[Move]) -> Result<Vec<String>, BoardError> {
+        let mut board = self.clone();
+        let mut line = Vec::new();
+
+        for &m in moves {
+            line.push(board.to_san(m)?);
+            board.play(m)?;
+        }
+
+        Ok(line)
+    }
+
+    /// Reads a sequence of SAN moves separated by whitespace. Move numbers,
+    /// as in "1. e4 e5 2. Nf3", are skipped.
+    pub fn parse_san_line(&self, line: &str) -> Result<Vec<Move>, SanError> {
+        let mut board = self.clone();
+        let mut moves = Vec::new();
+
+        for token in line.split_whitespace() {
+            let token = match token.rfind('.') {
+                Some(i) => &token[i + 1..],
+                None => token,
+            };
+            if token.is_empty() {
+                continue;
+            }
+
+            let m = board.parse_san(token)?;
+            board
+                .play(m)
+                .map_err(|_| SanError::Illegal(token.to_string()))?;
+            moves.push(m);
+        }
+
+        Ok(moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen.parse().unwrap()).unwrap()
+    }
+
+    fn san(board: &Board, m: &str) -> String {
+        board.to_san(m.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn to_san_test() {
+        let start = Board::new();
+        assert_eq!(san(&start, "g1f3"), "Nf3");
+        assert_eq!(san(&start, "e2e4"), "e4");
+
+        let mut b = Board::new();
+        for &m in ["e2e4", "d7d5"].iter() {
+            b.play(m.parse().unwrap()).unwrap();
+        }
+        assert_eq!(san(&b, "e4d5"), "exd5");
+
+        let castling = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(san(&castling, "e1g1"), "O-O");
+        assert_eq!(san(&castling, "e1c1"), "O-O-O");
+
+        let promotion = board("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(san(&promotion, "b7b8q"), "b8=Q+");
+        assert_eq!(san(&promotion, "b7b8n"), "b8=N");
+
+        let mut mate = Board::new();
+        for &m in ["f2f3", "e7e5", "g2g4"].iter() {
+            mate.play(m.parse().unwrap()).unwrap();
+        }
+        assert_eq!(san(&mate, "d8h4"), "Qh4#");
+    }
+
+    #[test]
+    fn disambiguation_test() {
+        let knights = board("rnbqkb1r/ppp1pppp/5n2/3p4/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq - 2 3");
+        assert_eq!(san(&knights, "b1d2"), "Nbd2");
+        assert_eq!(san(&knights, "f3d2"), "Nfd2");
+
+        let rooks = board("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+        assert_eq!(san(&rooks, "a1a3"), "R1a3");
+        assert_eq!(san(&rooks, "a5a3"), "R5a3");
+
+        let queens = board("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+        assert_eq!(san(&queens, "a1b2"), "Qa1b2");
+    }
+
+    #[test]
+    fn parse_san_test() {
+        let knights = board("rnbqkb1r/ppp1pppp/5n2/3p4/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq - 2 3");
+        assert_eq!(knights.parse_san("Nbd2"), Ok("b1d2".parse().unwrap()));
+        assert_eq!(knights.parse_san("Nf3-d2"), Ok("f3d2".parse().unwrap()));
+        assert_eq!(knights.parse_san("c4!?"), Ok("c2c4".parse().unwrap()));
+        assert_eq!(
+            knights.parse_san("Nd2"),
+            Err(SanError::Ambiguous("Nd2".to_string()))
+        );
+        assert_eq!(
+            knights.parse_san("Ne5+"),
+            Ok("f3e5".parse().unwrap())
+        );
+        assert_eq!(
+            knights.parse_san("Qh5"),
+            Err(SanError::Illegal("Qh5".to_string()))
+        );
+        assert_eq!(
+            knights.parse_san("Zz9"),
+            Err(SanError::Invalid("Zz9".to_string()))
+        );
+
+        let castling = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(castling.parse_san("O-O"), Ok("e1g1".parse().unwrap()));
+        assert_eq!(castling.parse_san("0-0-0"), Ok("e1c1".parse().unwrap()));
+
+        let promotion = board("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(promotion.parse_san("b8=Q+"), Ok("b7b8q".parse().unwrap()));
+        assert_eq!(
+            promotion.parse_san("b8"),
+            Err(SanError::Illegal("b8".to_string()))
+        );
+    }
+
+    #[test]
+    fn line_test() {
+        let start = Board::new();
+        let moves: Vec<Move> = "e2e4 e7e5 g1f3 b8c6 f1b5"
+            .split_whitespace()
+            .map(|m| m.parse().unwrap())
+            .collect();
+
+        let line = start.to_san_line(&moves).unwrap();
+        assert_eq!(line, vec!["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+        assert_eq!(start.parse_san_line("1. e4 e5 2.Nf3 Nc6 3. Bb5"), Ok(moves));
+    }
+}
@@ -0,0 +1,286 @@
+#[cfg(feature = "board")]
+use board::{Board, BoardError};
+use error::UciError;
+use fen::CastlingMode;
+use futures::future::{self, Either};
+use futures::{Async, Future, Poll, Stream};
+use go_params::GoParams;
+use parsers::{BestMove, EngineMessage, Move, SearchInfo};
+use position::Position;
+use std::time::Duration;
+use timeouts::TimeoutKind;
+use {parse_message, Engine};
+
+/// The position "bestmove" is checked against, when move validation is
+/// enabled.
+#[cfg(feature = "board")]
+pub(crate) type Validator = Option<Board>;
+#[cfg(not(feature = "board"))]
+pub(crate) type Validator = ();
+
+#[cfg(feature = "board")]
+pub(crate) fn validator(engine: &Engine) -> Result<Validator, UciError> {
+    match engine.position {
+        Some(ref position) if engine.validate_moves => Ok(Some(Board::from_position(
+            position,
+            engine.castling_mode(),
+        )?)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "board"))]
+pub(crate) fn validator(_engine: &Engine) -> Result<Validator, UciError> {
+    Ok(())
+}
+
+/// Checks that every move of `position` is legal.
+#[cfg(feature = "board")]
+pub(crate) fn validate_position(
+    position: &Position,
+    mode: CastlingMode,
+) -> Result<(), UciError> {
+    Board::from_position(position, mode)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "board"))]
+pub(crate) fn validate_position(
+    _position: &Position,
+    _mode: CastlingMode,
+) -> Result<(), UciError> {
+    Ok(())
+}
+
+#[cfg(feature = "board")]
+fn engine_move(e: BoardError) -> UciError {
+    match e {
+        BoardError::IllegalMove { illegal_move, fen } => {
+            UciError::IllegalMove { illegal_move, fen }
+        }
+        e => e.into(),
+    }
+}
+
+#[cfg(feature = "board")]
+fn check(validator: &Validator, best_move: &BestMove) -> Result<(), UciError> {
+    let mut board = match *validator {
+        Some(ref board) => board.clone(),
+        None => return Ok(()),
+    };
+
+    match best_move.best_move {
+        Some(m) => board.play(m).map_err(engine_move)?,
+        None if board.legal_moves().is_empty() => return Ok(()),
+        None => {
+            return Err(UciError::Protocol(
+                "no best move in a position with legal moves".to_string(),
+            ))
+        }
+    }
+    if let Some(ponder) = best_move.ponder {
+        board.play(ponder).map_err(engine_move)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "board"))]
+fn check(_validator: &Validator, _best_move: &BestMove) -> Result<(), UciError> {
+    Ok(())
+}
+
+/// A search started with `Engine::go`.
+///
+/// The handle is a `Stream` of the "info" lines sent while the search runs,
+/// which ends when the engine sends its "bestmove". It owns the engine until
+/// the search is over: `wait` resolves once the search finishes and `stop`
+/// ends it early, both giving the engine back along with the final
+/// `BestMove`.
+pub struct SearchHandle {
+    engine: Engine,
+    best_move: Option<BestMove>,
+    validator: Validator,
+}
+
+impl SearchHandle {
+    pub(crate) fn new(engine: Engine, validator: Validator) -> SearchHandle {
+        SearchHandle {
+            engine,
+            best_move: None,
+            validator,
+        }
+    }
+
+    /// Waits for the search to finish on its own.
+    pub fn wait(self) -> impl Future<Item = (Engine, BestMove), Error = UciError> {
+        let mut search = Some(self);
+
+        future::poll_fn(move || {
+            loop {
+                let info = search
+                    .as_mut()
+                    .expect("search polled after completion")
+                    .poll_info()?;
+
+                match info {
+                    Async::Ready(Some(_)) => continue,
+                    Async::Ready(None) => break,
+                    Async::NotReady => return Ok(Async::NotReady),
+                }
+            }
+
+            let search = search.take().unwrap();
+            let best_move = search.best_move.unwrap();
+            Ok(Async::Ready((search.engine, best_move)))
+        })
+    }
+
+    /// Sends "stop" to the engine and waits for the "bestmove" of the
+    /// running search, for no longer than the search grace period of the
+    /// engine's `Timeouts`.
+    ///
+    /// If the search already finished, its result is returned without
+    /// sending anything.
+    pub fn stop(self) -> impl Future<Item = (Engine, BestMove), Error = UciError> {
+        let SearchHandle {
+            mut engine,
+            best_move,
+            validator,
+        } = self;
+
+        match best_move {
+            Some(best_move) => Either::A(future::ok((engine, best_move))),
+            None => Either::B(
+                future::result(engine.start_timer(TimeoutKind::Search, Duration::from_secs(0)))
+                    .from_err()
+                    .and_then(move |()| engine.write("stop\n".to_string()))
+                    .and_then(|engine| SearchHandle::new(engine, validator).wait()),
+            ),
+        }
+    }
+
+    /// Tells a pondering engine that the opponent played the expected move.
+    pub fn ponderhit(self) -> impl Future<Item = SearchHandle, Error = UciError> {
+        let SearchHandle {
+            engine,
+            best_move,
+            validator,
+        } = self;
+
+        engine
+            .write("ponderhit\n".to_string())
+            .map(move |engine| SearchHandle {
+                engine,
+                best_move,
+                validator,
+            })
+    }
+
+    /// Reads the engine's output up to the next "info" line, or up to the
+    /// "bestmove" line in which case `None` is returned.
+    fn poll_info(&mut self) -> Poll<Option<SearchInfo>, UciError> {
+        while self.best_move.is_none() {
+            let line = match self.engine.poll_line()? {
+                Async::Ready(line) => line,
+                Async::NotReady => return Ok(Async::NotReady),
+            };
+
+            match parse_message(line)? {
+                EngineMessage::Info(info) => return Ok(Async::Ready(Some(info))),
+                EngineMessage::BestMove(best_move) => {
+                    self.engine.stop_timer();
+                    check(&self.validator, &best_move)?;
+                    self.best_move = Some(best_move);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Async::Ready(None))
+    }
+}
+
+impl Stream for SearchHandle {
+    type Item = SearchInfo;
+    type Error = UciError;
+
+    fn poll(&mut self) -> Poll<Option<SearchInfo>, UciError> {
+        self.poll_info()
+    }
+}
+
+/// A "go ponder" search started with `Engine::go_ponder`, on the position
+/// after the move the engine expects its opponent to play.
+///
+/// Once the opponent moved, `opponent_moved` turns it into a regular search
+/// of the actual position.
+pub struct PonderHandle {
+    search: SearchHandle,
+    position: Position,
+    ponder: Move,
+}
+
+impl PonderHandle {
+    pub(crate) fn new(search: SearchHandle, position: Position, ponder: Move) -> PonderHandle {
+        PonderHandle {
+            search,
+            position,
+            ponder,
+        }
+    }
+
+    /// The move the engine expects its opponent to play.
+    pub fn ponder_move(&self) -> Move {
+        self.ponder
+    }
+
+    /// Continues the game once the opponent played `played`.
+    ///
+    /// If it is the ponder move, "ponderhit" is sent and the search goes on.
+    /// Otherwise the pondering search is stopped, its result thrown away,
+    /// and a new search of the actual position is started with `params`.
+    /// `params` should hold the clocks as they are after the opponent's
+    /// move, and sets the search timeout in both cases.
+    pub fn opponent_moved(
+        self,
+        played: Move,
+        params: GoParams,
+    ) -> impl Future<Item = SearchHandle, Error = UciError> {
+        let PonderHandle {
+            search,
+            mut position,
+            ponder,
+        } = self;
+        position.push(played);
+
+        if played == ponder {
+            let side = position.side_to_move();
+
+            Either::A(
+                future::result(params.validate())
+                    .from_err()
+                    .and_then(move |()| search.ponderhit())
+                    .and_then(move |mut search| -> Result<SearchHandle, UciError> {
+                        if let Some(limit) = params.time_limit(side) {
+                            search.engine.start_timer(TimeoutKind::Search, limit)?;
+                        }
+                        Ok(search)
+                    }),
+            )
+        } else {
+            Either::B(
+                search
+                    .stop()
+                    .and_then(move |(engine, _)| engine.set_position(position))
+                    .and_then(move |engine| engine.go(params)),
+            )
+        }
+    }
+
+    /// Stops pondering and throws the result away, for instance because the
+    /// game is over.
+    pub fn stop(self) -> impl Future<Item = Engine, Error = UciError> {
+        self.search.stop().map(|(engine, _)| engine)
+    }
+}
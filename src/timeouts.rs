@@ -0,0 +1,141 @@
+use futures::{Future, Poll};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use tokio_core::reactor::{Handle, Timeout};
+
+/// What the engine failed to answer in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    /// The "uci" command was not answered with "uciok".
+    Handshake,
+    /// The "isready" command was not answered with "readyok".
+    Sync,
+    /// No "bestmove" came within the search's time limit and grace period.
+    Search,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match *self {
+            TimeoutKind::Handshake => "the handshake",
+            TimeoutKind::Sync => "isready",
+            TimeoutKind::Search => "the search",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// How long to wait for the engine before giving up with
+/// `UciError::Timeout`.
+///
+/// Nothing times out unless a duration is set. By default the engine is
+/// killed when it times out, since it is most likely hung.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate async_uci;
+/// extern crate tokio_core;
+///
+/// use async_uci::Timeouts;
+/// use std::time::Duration;
+/// use tokio_core::reactor::Core;
+///
+/// fn main() {
+///     let core = Core::new().unwrap();
+///     let timeouts = Timeouts::new(&core.handle())
+///         .handshake(Duration::from_secs(10))
+///         .sync(Duration::from_secs(5))
+///         .search_grace(Duration::from_secs(1));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    handle: Handle,
+    handshake: Option<Duration>,
+    sync: Option<Duration>,
+    search_grace: Option<Duration>,
+    kill: bool,
+}
+
+impl Timeouts {
+    /// Timers run on the reactor `handle` belongs to.
+    pub fn new(handle: &Handle) -> Timeouts {
+        Timeouts {
+            handle: handle.clone(),
+            handshake: None,
+            sync: None,
+            search_grace: None,
+            kill: true,
+        }
+    }
+
+    /// The time allowed between "uci" and "uciok".
+    pub fn handshake(mut self, timeout: Duration) -> Timeouts {
+        self.handshake = Some(timeout);
+        self
+    }
+
+    /// The time allowed between "isready" and "readyok".
+    pub fn sync(mut self, timeout: Duration) -> Timeouts {
+        self.sync = Some(timeout);
+        self
+    }
+
+    /// The time allowed for "bestmove" once a search should be over: after
+    /// its "movetime" or the side to move's remaining time, or after "stop"
+    /// is sent.
+    ///
+    /// Searches limited only by "depth", "nodes" or "mate" have no time
+    /// limit, so they never time out until they are stopped.
+    pub fn search_grace(mut self, grace: Duration) -> Timeouts {
+        self.search_grace = Some(grace);
+        self
+    }
+
+    /// Whether to kill the engine process when it times out. Otherwise the
+    /// process is left running on its own.
+    pub fn kill_on_timeout(mut self, kill: bool) -> Timeouts {
+        self.kill = kill;
+        self
+    }
+
+    pub(crate) fn kills(&self) -> bool {
+        self.kill
+    }
+
+    /// Starts a timer for `kind` if a timeout is set for it, adding `extra`
+    /// to its duration.
+    pub(crate) fn deadline(
+        &self,
+        kind: TimeoutKind,
+        extra: Duration,
+    ) -> io::Result<Option<Deadline>> {
+        let duration = match kind {
+            TimeoutKind::Handshake => self.handshake,
+            TimeoutKind::Sync => self.sync,
+            TimeoutKind::Search => self.search_grace,
+        };
+
+        match duration {
+            Some(duration) => Ok(Some(Deadline {
+                timer: Timeout::new(duration + extra, &self.handle)?,
+                kind,
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A running timer for a command the engine has to answer.
+pub(crate) struct Deadline {
+    timer: Timeout,
+    pub(crate) kind: TimeoutKind,
+}
+
+impl Deadline {
+    pub(crate) fn poll(&mut self) -> Poll<(), io::Error> {
+        self.timer.poll()
+    }
+}
@@ -0,0 +1,682 @@
+use board::Board;
+use clock::GameClock;
+use error::UciError;
+use fen::{CastlingMode, Color};
+use futures::prelude::*;
+use futures::{future, stream};
+use game_match::{finish, play_game, Termination};
+use log::SilentLogger;
+use pgn::{GameResult, PgnGame};
+use position::Position;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+use timeouts::Timeouts;
+use tokio_core::reactor::Handle;
+use Engine;
+
+/// Starts a new engine process for a game, in the tournament's castling
+/// mode.
+type Spawn = Fn(&Handle, CastlingMode) -> Box<Future<Item = Engine, Error = UciError>>;
+
+/// Who plays whom in a tournament.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Every engine plays every other engine.
+    RoundRobin,
+    /// The first engine added plays every other engine, which do not play
+    /// each other.
+    Gauntlet,
+}
+
+/// A game of a tournament's schedule. Engines are numbered in the order
+/// they were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pairing {
+    /// The game's number, starting at 1.
+    pub number: usize,
+    pub round: u32,
+    pub white: usize,
+    pub black: usize,
+    pub opening: Position,
+}
+
+/// A finished game of a tournament.
+pub struct TournamentGame {
+    pub pairing: Pairing,
+    pub game: PgnGame,
+    pub termination: Termination,
+    /// What went wrong with the engine that lost, if it failed or could not
+    /// be started.
+    pub error: Option<UciError>,
+}
+
+/// The results of a tournament.
+pub struct TournamentResult {
+    /// The games in the order they were scheduled.
+    pub games: Vec<TournamentGame>,
+    pub crosstable: Crosstable,
+}
+
+struct Player {
+    name: String,
+    spawn: Box<Spawn>,
+}
+
+/// Plays games between several engines, a few at a time, on one reactor.
+///
+/// Each pairing plays every opening twice with the colors swapped. Engines
+/// are started for every game and quit after it, so a crash only costs the
+/// game it happened in.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// extern crate async_uci;
+/// extern crate tokio_core;
+///
+/// use async_uci::{Format, GameClock, TimeBonus, Tournament};
+/// use std::time::Duration;
+/// use tokio_core::reactor::Core;
+///
+/// fn main() {
+///     let mut core = Core::new().unwrap();
+///     let clock = GameClock::sudden_death(Duration::from_secs(10))
+///         .bonus(TimeBonus::Fischer(Duration::from_millis(100)));
+///
+///     let tournament = Tournament::new(&core.handle(), clock)
+///         .add_engine("Stockfish", "stockfish")
+///         .add_engine("Ethereal", "ethereal")
+///         .add_engine("Laser", "laser")
+///         .format(Format::RoundRobin)
+///         .rounds(10)
+///         .concurrency(4);
+///
+///     let result = core.run(tournament.run()).unwrap();
+///     print!("{}", result.crosstable);
+/// }
+/// ```
+pub struct Tournament {
+    handle: Handle,
+    clock: GameClock,
+    players: Vec<Rc<Player>>,
+    format: Format,
+    openings: Vec<Position>,
+    rounds: u32,
+    concurrency: usize,
+    event: Option<String>,
+    castling_mode: CastlingMode,
+}
+
+impl Tournament {
+    /// A round-robin tournament of one round from the starting position,
+    /// playing one game at a time with `clock`.
+    pub fn new(handle: &Handle, clock: GameClock) -> Tournament {
+        Tournament {
+            handle: handle.clone(),
+            clock,
+            players: Vec::new(),
+            format: Format::RoundRobin,
+            openings: vec![Position::startpos()],
+            rounds: 1,
+            concurrency: 1,
+            event: None,
+            castling_mode: CastlingMode::Standard,
+        }
+    }
+
+    /// Adds an engine started from the executable at `path`, in the
+    /// tournament's castling mode.
+    ///
+    /// The engine has 10 seconds to answer "uci" and "isready", and loses a
+    /// game on time when it does not answer within a second after its time
+    /// ran out.
+    pub fn add_engine(self, name: &str, path: &str) -> Tournament {
+        let path = path.to_string();
+        self.add_player(name, move |handle: &Handle, mode: CastlingMode| {
+            spawn_engine(path.clone(), handle, mode)
+        })
+    }
+
+    /// Adds an engine started by `spawn`, which can set options or
+    /// timeouts before handing it over. The engine must be started in the
+    /// tournament's castling mode.
+    pub fn add_engine_with<F>(self, name: &str, spawn: F) -> Tournament
+    where
+        F: Fn(&Handle) -> Box<Future<Item = Engine, Error = UciError>> + 'static,
+    {
+        self.add_player(name, move |handle: &Handle, _: CastlingMode| spawn(handle))
+    }
+
+    fn add_player<F>(mut self, name: &str, spawn: F) -> Tournament
+    where
+        F: Fn(&Handle, CastlingMode) -> Box<Future<Item = Engine, Error = UciError>> + 'static,
+    {
+        self.players.push(Rc::new(Player {
+            name: name.to_string(),
+            spawn: Box::new(spawn),
+        }));
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Tournament {
+        self.format = format;
+        self
+    }
+
+    /// The positions every pairing plays from, twice each.
+    pub fn openings(mut self, openings: Vec<Position>) -> Tournament {
+        self.openings = openings;
+        self
+    }
+
+    /// How many times the whole schedule is played.
+    pub fn rounds(mut self, rounds: u32) -> Tournament {
+        self.rounds = rounds;
+        self
+    }
+
+    /// How many games are played at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `games` is 0.
+    pub fn concurrency(mut self, games: usize) -> Tournament {
+        assert!(games > 0, "a tournament needs to play at least one game at a time");
+        self.concurrency = games;
+        self
+    }
+
+    /// Sets the "Event" tag of every game.
+    pub fn event(mut self, event: &str) -> Tournament {
+        self.event = Some(event.to_string());
+        self
+    }
+
+    /// The castling mode the openings are read in, which is
+    /// `CastlingMode::Standard` unless changed.
+    pub fn castling_mode(mut self, mode: CastlingMode) -> Tournament {
+        self.castling_mode = mode;
+        self
+    }
+
+    /// Every game to be played, in order.
+    pub fn pairings(&self) -> Vec<Pairing> {
+        let count = self.players.len();
+        let mut opponents = Vec::new();
+        for first in 0..count {
+            for second in first + 1..count {
+                if self.format == Format::RoundRobin || first == 0 {
+                    opponents.push((first, second));
+                }
+            }
+        }
+
+        let mut pairings = Vec::new();
+        for round in 1..self.rounds + 1 {
+            for opening in &self.openings {
+                for &(first, second) in &opponents {
+                    for &(white, black) in &[(first, second), (second, first)] {
+                        let number = pairings.len() + 1;
+                        pairings.push(Pairing {
+                            number,
+                            round,
+                            white,
+                            black,
+                            opening: opening.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        pairings
+    }
+
+    /// Plays every game of the schedule. Only an invalid opening makes the
+    /// future fail, before any game starts.
+    pub fn run(self) -> impl Future<Item = TournamentResult, Error = UciError> {
+        future::result(self.check_openings()).and_then(move |()| self.play())
+    }
+
+    fn check_openings(&self) -> Result<(), UciError> {
+        for opening in &self.openings {
+            Board::from_position(opening, self.castling_mode)?;
+        }
+        Ok(())
+    }
+
+    fn play(self) -> impl Future<Item = TournamentResult, Error = UciError> {
+        let names: Vec<String> = self.players.iter().map(|player| player.name.clone()).collect();
+        let games: Vec<ScheduledGame> = self.pairings()
+            .into_iter()
+            .map(|pairing| ScheduledGame {
+                white: self.players[pairing.white].clone(),
+                black: self.players[pairing.black].clone(),
+                clock: self.clock.clone(),
+                event: self.event.clone(),
+                castling_mode: self.castling_mode,
+                pairing,
+            })
+            .collect();
+
+        let handle = self.handle;
+        stream::iter_ok::<_, UciError>(games)
+            .map(move |game| play_scheduled(game, handle.clone()))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .map(move |mut games| {
+                games.sort_by_key(|game| game.pairing.number);
+
+                let mut crosstable = Crosstable::new(names);
+                for game in &games {
+                    crosstable.add(game.pairing.white, game.pairing.black, game.game.result());
+                }
+
+                TournamentResult { games, crosstable }
+            })
+    }
+}
+
+fn spawn_engine(
+    path: String,
+    handle: &Handle,
+    mode: CastlingMode,
+) -> Box<Future<Item = Engine, Error = UciError>> {
+    let timeouts = Timeouts::new(handle)
+        .handshake(Duration::from_secs(10))
+        .sync(Duration::from_secs(10))
+        .search_grace(Duration::from_secs(1));
+    let chess960 = mode == CastlingMode::Chess960;
+
+    Box::new(
+        future::result(Engine::spawn(path, handle)).and_then(move |process| {
+            Engine::handshake(process, Box::new(SilentLogger), Some(timeouts), chess960)
+        }),
+    )
+}
+
+struct ScheduledGame {
+    pairing: Pairing,
+    white: Rc<Player>,
+    black: Rc<Player>,
+    clock: GameClock,
+    event: Option<String>,
+    castling_mode: CastlingMode,
+}
+
+/// Plays a game whose opening was checked by `Tournament::run`, which is all
+/// that can make it fail.
+#[async]
+fn play_scheduled(scheduled: ScheduledGame, handle: Handle) -> Result<TournamentGame, UciError> {
+    let ScheduledGame {
+        pairing,
+        white,
+        black,
+        clock,
+        event,
+        castling_mode,
+    } = scheduled;
+
+    let mut engines = Vec::new();
+    let mut failed = None;
+    for (side, player) in vec![(Color::White, white.clone()), (Color::Black, black.clone())] {
+        match await!((player.spawn)(&handle, castling_mode)) {
+            Ok(engine) => engines.push(engine),
+            Err(e) => {
+                failed = Some((side, e));
+                break;
+            }
+        }
+    }
+
+    let (mut game, termination, error) = match failed {
+        None => {
+            let black_engine = engines.pop().unwrap();
+            let white_engine = engines.pop().unwrap();
+            let record = await!(play_game(
+                white_engine,
+                black_engine,
+                pairing.opening.clone(),
+                clock
+            ))?;
+
+            engines.extend(record.white);
+            engines.extend(record.black);
+            (record.game, record.termination, record.error)
+        }
+        Some((loser, e)) => {
+            let termination = Termination::Crash { loser };
+            let mut game = PgnGame::from_position(&pairing.opening, castling_mode)?;
+            finish(&mut game, termination);
+            (game, termination, Some(e))
+        }
+    };
+
+    for engine in engines {
+        // The game is over either way, so an engine failing to quit
+        // cleanly does not change anything.
+        let _ = await!(engine.quit());
+    }
+
+    game.set_tag("White", &white.name);
+    game.set_tag("Black", &black.name);
+    game.set_tag("Round", &pairing.round.to_string());
+    if let Some(event) = event {
+        game.set_tag("Event", &event);
+    }
+
+    Ok(TournamentGame {
+        pairing,
+        game,
+        termination,
+        error,
+    })
+}
+
+/// Wins, draws and losses, from one engine's point of view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Twice the number of points, so that it stays an integer.
+    pub fn half_points(&self) -> u32 {
+        2 * self.wins + self.draws
+    }
+}
+
+/// Writes the number of points, such as "2.5".
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let half_points = self.half_points();
+        let points = if half_points % 2 == 0 {
+            format!("{}", half_points / 2)
+        } else {
+            format!("{}.5", half_points / 2)
+        };
+        f.pad(&points)
+    }
+}
+
+/// The score of every engine against every other engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crosstable {
+    names: Vec<String>,
+    tallies: Vec<Vec<Tally>>,
+}
+
+impl Crosstable {
+    pub fn new(names: Vec<String>) -> Crosstable {
+        let count = names.len();
+        Crosstable {
+            names,
+            tallies: vec![vec![Tally::default(); count]; count],
+        }
+    }
+
+    /// Counts a game between the engines numbered `white` and `black`.
+    /// Unfinished games are not counted.
+    pub fn add(&mut self, white: usize, black: usize, result: GameResult) {
+        match result {
+            GameResult::WhiteWins => {
+                self.tallies[white][black].wins += 1;
+                self.tallies[black][white].losses += 1;
+            }
+            GameResult::BlackWins => {
+                self.tallies[white][black].losses += 1;
+                self.tallies[black][white].wins += 1;
+            }
+            GameResult::Draw => {
+                self.tallies[white][black].draws += 1;
+                self.tallies[black][white].draws += 1;
+            }
+            GameResult::Unfinished => {}
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The score of `player` against `opponent`.
+    pub fn head_to_head(&self, player: usize, opponent: usize) -> Tally {
+        self.tallies[player][opponent]
+    }
+
+    /// The score of `player` against everyone.
+    pub fn total(&self, player: usize) -> Tally {
+        self.tallies[player].iter().fold(Tally::default(), |total, tally| Tally {
+            wins: total.wins + tally.wins,
+            draws: total.draws + tally.draws,
+            losses: total.losses + tally.losses,
+        })
+    }
+
+    /// The engines from the most points to the fewest. Ties keep the order
+    /// the engines were added in.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.names.len()).collect();
+        standings.sort_by(|&a, &b| {
+            self.total(b)
+                .half_points()
+                .cmp(&self.total(a).half_points())
+        });
+        standings
+    }
+}
+
+/// Writes a table in the order of the standings, where each column gives the
+/// points scored against the engine of that rank.
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let standings = self.standings();
+        let width = self.names
+            .iter()
+            .map(|name| name.len())
+            .chain(Some("Engine".len()))
+            .max()
+            .unwrap();
+
+        write!(f, "{:>2}  {:<2$}  Points  Games", "#", "Engine", width)?;
+        for rank in 1..standings.len() + 1 {
+            write!(f, "  {:>5}", rank)?;
+        }
+        writeln!(f)?;
+
+        for (rank, &player) in standings.iter().enumerate() {
+            let total = self.total(player);
+            write!(
+                f,
+                "{:>2}  {:<4$}  {:>6}  {:>5}",
+                rank + 1,
+                self.names[player],
+                total,
+                total.games(),
+                width
+            )?;
+
+            for &opponent in &standings {
+                let tally = self.tallies[player][opponent];
+                if opponent == player {
+                    write!(f, "  {:>5}", "x")?;
+                } else if tally.games() == 0 {
+                    write!(f, "  {:>5}", "-")?;
+                } else {
+                    write!(f, "  {:>5}", tally)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Outcome;
+    use fen::Fen;
+    use std::time::Duration;
+    use tests::fake_engine;
+    use tokio_core::reactor::Core;
+
+    fn tournament(core: &Core, names: &[&str]) -> Tournament {
+        let clock = GameClock::sudden_death(Duration::from_secs(10));
+        names.iter().fold(Tournament::new(&core.handle(), clock), |tournament, name| {
+            tournament.add_engine(name, "/nonexistent/engine")
+        })
+    }
+
+    #[test]
+    fn round_robin_test() {
+        let core = Core::new().unwrap();
+        let pairings = tournament(&core, &["A", "B", "C"])
+            .openings(vec![Position::startpos(), Position::startpos()])
+            .rounds(2)
+            .pairings();
+
+        assert_eq!(pairings.len(), 24);
+        assert_eq!(pairings[0].number, 1);
+        assert_eq!((pairings[0].white, pairings[0].black), (0, 1));
+        assert_eq!((pairings[1].white, pairings[1].black), (1, 0));
+        assert_eq!((pairings[5].white, pairings[5].black), (2, 1));
+        assert_eq!(pairings[11].round, 1);
+        assert_eq!(pairings[12].round, 2);
+        for player in 0..3 {
+            let white = pairings.iter().filter(|p| p.white == player).count();
+            let black = pairings.iter().filter(|p| p.black == player).count();
+            assert_eq!((white, black), (8, 8));
+        }
+    }
+
+    #[test]
+    fn gauntlet_test() {
+        let core = Core::new().unwrap();
+        let pairings = tournament(&core, &["A", "B", "C", "D"])
+            .format(Format::Gauntlet)
+            .pairings();
+
+        let games: Vec<(usize, usize)> = pairings.iter().map(|p| (p.white, p.black)).collect();
+        assert_eq!(games, vec![(0, 1), (1, 0), (0, 2), (2, 0), (0, 3), (3, 0)]);
+    }
+
+    #[test]
+    fn crosstable_test() {
+        let names = vec!["Alpha".to_string(), "Beta".to_string(), "Gamma".to_string()];
+        let mut crosstable = Crosstable::new(names);
+        crosstable.add(0, 1, GameResult::WhiteWins);
+        crosstable.add(1, 0, GameResult::BlackWins);
+        crosstable.add(0, 2, GameResult::Draw);
+        crosstable.add(2, 0, GameResult::Draw);
+        crosstable.add(1, 2, GameResult::WhiteWins);
+        crosstable.add(2, 1, GameResult::WhiteWins);
+        crosstable.add(2, 1, GameResult::Unfinished);
+
+        assert_eq!(
+            crosstable.total(2),
+            Tally {
+                wins: 1,
+                draws: 2,
+                losses: 1,
+            }
+        );
+        assert_eq!(crosstable.head_to_head(1, 0).to_string(), "0");
+        assert_eq!(crosstable.standings(), vec![0, 2, 1]);
+        assert_eq!(
+            crosstable.to_string(),
+            concat!(
+                " #  Engine  Points  Games      1      2      3\n",
+                " 1  Alpha        3      4      x      1      2\n",
+                " 2  Gamma        2      4      1      x      1\n",
+                " 3  Beta         1      4      0      1      x\n",
+            )
+        );
+
+        let mut half = Crosstable::new(vec!["A".to_string(), "B".to_string()]);
+        half.add(0, 1, GameResult::Draw);
+        assert_eq!(half.total(0).to_string(), "0.5");
+    }
+
+    const MATE_IN_ONE: &str = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1";
+
+    /// An engine that always plays the mate of `MATE_IN_ONE`.
+    fn mating_engine(handle: &Handle) -> Box<Future<Item = Engine, Error = UciError>> {
+        let script = "while read command rest; do case $command in \
+                      uci) echo uciok;; isready) echo readyok;; \
+                      go) echo 'bestmove d1d8';; quit) exit;; esac; done";
+        Box::new(Engine::new(fake_engine(handle, script)))
+    }
+
+    #[test]
+    fn tournament_test() {
+        let mut core = Core::new().unwrap();
+        let fen: Fen = MATE_IN_ONE.parse().unwrap();
+        let clock = GameClock::sudden_death(Duration::from_secs(10));
+        let tournament = Tournament::new(&core.handle(), clock)
+            .add_engine_with("White", mating_engine)
+            .add_engine_with("Black", mating_engine)
+            .openings(vec![Position::fen(fen)])
+            .concurrency(2)
+            .event("Test");
+
+        let result = core.run(tournament.run()).unwrap();
+        assert_eq!(result.games.len(), 2);
+        for game in &result.games {
+            assert_eq!(
+                game.termination,
+                Termination::Outcome(Outcome::Checkmate {
+                    winner: Color::White,
+                })
+            );
+            assert_eq!(game.game.tag("Event"), Some("Test"));
+            assert_eq!(game.game.tag("Round"), Some("1"));
+        }
+        assert_eq!(result.games[1].game.tag("White"), Some("Black"));
+        assert_eq!(result.crosstable.total(0).half_points(), 2);
+        assert_eq!(result.crosstable.total(1).half_points(), 2);
+    }
+
+    #[test]
+    fn spawn_failure_test() {
+        let mut core = Core::new().unwrap();
+        let clock = GameClock::sudden_death(Duration::from_secs(10));
+        let tournament = Tournament::new(&core.handle(), clock)
+            .add_engine("Missing", "/nonexistent/engine")
+            .add_engine_with("Mater", mating_engine)
+            .castling_mode(CastlingMode::Chess960);
+
+        let result = core.run(tournament.run()).unwrap();
+        assert_eq!(result.games[0].termination, Termination::Crash { loser: Color::White });
+        assert_eq!(result.games[1].termination, Termination::Crash { loser: Color::Black });
+        for game in &result.games {
+            assert_eq!(game.game.start().castling_mode(), CastlingMode::Chess960);
+            match game.error {
+                Some(UciError::Spawn(_)) => {}
+                ref other => panic!("unexpected error {:?}", other),
+            }
+        }
+        assert_eq!(result.crosstable.total(1).half_points(), 4);
+    }
+
+    #[test]
+    fn invalid_opening_test() {
+        let mut core = Core::new().unwrap();
+        let mut opening = Position::startpos();
+        opening.push("e2e5".parse().unwrap());
+        let tournament = tournament(&core, &["A", "B"]).openings(vec![opening]);
+
+        match core.run(tournament.run()) {
+            Err(UciError::Position { .. }) => {}
+            _ => panic!("tournament started with an illegal opening"),
+        }
+    }
+}